@@ -11,6 +11,7 @@ use which::which;
 use grib::context::{Grib2, GribError};
 use grib::reader::SeekableGrib2Reader;
 
+#[allow(clippy::enum_variant_names)]
 pub enum CliError {
     GribError(GribError),
     ParseNumberError(ParseIntError),
@@ -41,7 +42,7 @@ impl From<ParseIntError> for CliError {
 
 pub fn grib(file_name: &str) -> Result<Grib2<SeekableGrib2Reader<BufReader<File>>>, CliError> {
     let path = Path::new(file_name);
-    let f = File::open(&path).map_err(|e| CliError::IOError(e, path.display().to_string()))?;
+    let f = File::open(path).map_err(|e| CliError::IOError(e, path.display().to_string()))?;
     let f = BufReader::new(f);
     Ok(Grib2::<SeekableGrib2Reader<BufReader<File>>>::read_with_seekable(f)?)
 }
@@ -2,6 +2,8 @@ use clap::{App, Arg, ArgMatches, SubCommand};
 use std::fs::File;
 use std::io::Write;
 
+use grib::decoder::MissingValue;
+
 use crate::cli;
 
 pub fn cli() -> App<'static, 'static> {
@@ -24,20 +26,42 @@ pub fn cli() -> App<'static, 'static> {
                 .takes_value(true)
                 .conflicts_with("big-endian"),
         )
+        .arg(
+            Arg::with_name("missing")
+                .help("Value used for missing values, including NaN in the data, such as 9.999e20 [default: nan]")
+                .long("missing")
+                .takes_value(true)
+                .value_name("VALUE")
+                .validator(|value| parse_missing(&value).map(|_| ())),
+        )
+}
+
+fn parse_missing(value: &str) -> Result<MissingValue, String> {
+    if value.eq_ignore_ascii_case("nan") {
+        return Ok(MissingValue::Nan);
+    }
+    value
+        .parse()
+        .map(MissingValue::Value)
+        .map_err(|_| format!("invalid missing value: {}", value))
 }
 
 pub fn exec(args: &ArgMatches<'static>) -> Result<(), cli::CliError> {
     let file_name = args.value_of("file").unwrap();
     let grib = cli::grib(file_name)?;
     let index: usize = args.value_of("index").unwrap().parse()?;
-    let values = grib.get_values(index)?;
+    let missing = match args.value_of("missing") {
+        Some(value) => parse_missing(value).unwrap(),
+        None => MissingValue::default(),
+    };
+    let values = grib.get_values_with_missing(index, missing)?;
 
     if args.is_present("big-endian") {
         let out_path = args.value_of("big-endian").unwrap();
         File::create(out_path)
             .and_then(|mut f| {
                 for value in values.iter() {
-                    f.write(&value.to_be_bytes())?;
+                    f.write_all(&value.to_be_bytes())?;
                 }
                 Ok(())
             })
@@ -47,7 +71,7 @@ pub fn exec(args: &ArgMatches<'static>) -> Result<(), cli::CliError> {
         File::create(out_path)
             .and_then(|mut f| {
                 for value in values.iter() {
-                    f.write(&value.to_le_bytes())?;
+                    f.write_all(&value.to_le_bytes())?;
                 }
                 Ok(())
             })
@@ -12,5 +12,17 @@ pub fn exec(args: &ArgMatches<'static>) -> Result<(), cli::CliError> {
     let file_name = args.value_of("file").unwrap();
     let grib = cli::grib(file_name)?;
     println!("{}", grib);
+
+    for i in 0..grib.submessages().len() {
+        let satellite = match grib.prod_def(i).and_then(|p| p.satellite_product()) {
+            Some(satellite) => satellite,
+            None => continue,
+        };
+        println!();
+        println!("Satellite spectral bands of submessage {}:", i);
+        for band in satellite.bands.iter() {
+            println!("  {}", band);
+        }
+    }
     Ok(())
 }
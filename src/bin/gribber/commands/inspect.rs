@@ -42,7 +42,7 @@ pub fn exec(args: &ArgMatches<'static>) -> Result<(), cli::CliError> {
         let tmpls = grib.list_templates();
         view.add(InspectItem::Templates(tmpls));
     }
-    if view.items.len() == 0 {
+    if view.items.is_empty() {
         view.add(InspectItem::Sections(grib.sections()));
         let tmpls = grib.list_templates();
         view.add(InspectItem::Templates(tmpls));
@@ -87,8 +87,8 @@ pub fn exec(args: &ArgMatches<'static>) -> Result<(), cli::CliError> {
             }
         }
 
-        if let Some(_) = items.peek() {
-            println!("");
+        if items.peek().is_some() {
+            println!();
         }
     }
 
@@ -109,7 +109,7 @@ impl<'i> InspectView<'i> {
     }
 
     fn with_headers(&self) -> bool {
-        !(self.items.len() < 2)
+        self.items.len() >= 2
     }
 
     fn num_lines(&self) -> usize {
@@ -126,7 +126,7 @@ impl<'i> InspectView<'i> {
 }
 
 enum InspectItem<'i> {
-    Sections(&'i Box<[SectionInfo]>),
+    Sections(&'i [SectionInfo]),
     Templates(Vec<TemplateInfo>),
 }
 
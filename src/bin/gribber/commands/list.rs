@@ -1,16 +1,110 @@
 use clap::{App, Arg, ArgMatches, SubCommand};
 
+use grib::codetables::lookup_parameter;
+use grib::context::Grib2;
+use grib::reader::Grib2Read;
+
 use crate::cli;
 
 pub fn cli() -> App<'static, 'static> {
     SubCommand::with_name("list")
         .about("Lists contained data")
+        .arg(
+            Arg::with_name("dump")
+                .help("Dumps structure of submessages")
+                .short("d")
+                .long("dump"),
+        )
         .arg(Arg::with_name("file").required(true))
 }
 
 pub fn exec(args: &ArgMatches<'static>) -> Result<(), cli::CliError> {
     let file_name = args.value_of("file").unwrap();
     let grib = cli::grib(file_name)?;
-    println!("{:#?}", grib.submessages());
+    if args.is_present("dump") {
+        println!("{:#?}", grib.submessages());
+        return Ok(());
+    }
+
+    for i in 0..grib.submessages().len() {
+        println!("{}", summary(&grib, i));
+    }
     Ok(())
 }
+
+fn summary<R: Grib2Read>(grib: &Grib2<R>, i: usize) -> String {
+    let mut columns = vec![i.to_string()];
+    let prod_def = match grib.prod_def(i) {
+        Some(prod_def) => prod_def,
+        None => return columns.join("  "),
+    };
+    columns.push(format!("4.{}", prod_def.prod_tmpl_num));
+
+    let satellite = prod_def.satellite_product();
+    let parameter = match (prod_def.analysis_or_forecast(), &satellite) {
+        (Some(p), _) => Some((
+            p.parameter_category,
+            p.parameter_number,
+            Some(p.forecast_time),
+        )),
+        (_, Some(p)) => Some((
+            p.parameter_category,
+            p.parameter_number,
+            p.forecast_time.clone(),
+        )),
+        _ => None,
+    };
+
+    let mut unit = None;
+    if let Some((category, number, forecast_time)) = parameter {
+        let discipline = grib.indicator().map_or(0, |s| s.discipline);
+        match lookup_parameter(discipline, category, number) {
+            Some((name, parameter_unit)) => {
+                columns.push(name.to_owned());
+                unit = Some(parameter_unit);
+            }
+            None => columns.push(format!("{}.{}.{}", discipline, category, number)),
+        }
+        if let Some(forecast_time) = forecast_time {
+            columns.push(forecast_time.to_string());
+        }
+    }
+
+    if let Some(level) = prod_def
+        .analysis_or_forecast()
+        .and_then(|p| p.level_or_layer())
+    {
+        columns.push(level.to_string());
+    }
+
+    if let Some(satellite) = satellite {
+        let wave_numbers: Vec<_> = satellite
+            .bands
+            .iter()
+            .map(|band| {
+                band.central_wave_number
+                    .value()
+                    .map_or_else(|| "missing".to_owned(), |v| v.to_string())
+            })
+            .collect();
+        columns.push(format!("wave number {} m-1", wave_numbers.join(",")));
+    }
+
+    let constituent = match (prod_def.chemical_constituent(), prod_def.aerosol()) {
+        (Some(c), _) => Some((c.name(), c.constituent_type)),
+        (_, Some(a)) => Some((a.name(), a.aerosol_type)),
+        _ => None,
+    };
+    if let Some((name, code)) = constituent {
+        columns.push(name.map_or_else(|| format!("constituent {}", code), str::to_owned));
+    }
+
+    if let Some(threshold) = prod_def.probability_forecast().and_then(|p| p.threshold()) {
+        match unit {
+            Some(unit) => columns.push(format!("{} {}", threshold, unit)),
+            None => columns.push(threshold.to_string()),
+        }
+    }
+
+    columns.join("  ")
+}
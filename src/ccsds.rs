@@ -0,0 +1,471 @@
+//! Decoding of data compressed with the lossless adaptive entropy coder
+//! of CCSDS 121.0-B, which is implemented in libaec and used by Data
+//! Representation Template 5.42.
+
+use crate::decoder::CcsdsDecodeError;
+use crate::utils::BitReader;
+
+// Flags of the CCSDS compression options mask, which are the same as those
+// of libaec.  The flags for the byte order and the sample size of the
+// output buffer of libaec do not affect decoding into integers.
+pub(crate) const DATA_SIGNED: u8 = 1;
+pub(crate) const DATA_PREPROCESS: u8 = 8;
+pub(crate) const RESTRICTED: u8 = 16;
+pub(crate) const PAD_RSI: u8 = 32;
+pub(crate) const NOT_ENFORCE: u8 = 64;
+
+/// Number of blocks of a segment, at the end of which a run of zero blocks
+/// coded as "remainder of segment" ends.
+const SEGMENT_SIZE: usize = 64;
+/// Zero block count value meaning "remainder of segment".
+const ROS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AecParam {
+    pub(crate) bits_per_sample: u8,
+    pub(crate) block_size: u8,
+    /// Reference sample interval, i.e. the number of blocks between
+    /// reference samples
+    pub(crate) rsi: u16,
+    pub(crate) flags: u8,
+}
+
+impl AecParam {
+    fn validate(&self) -> Result<(), CcsdsDecodeError> {
+        if self.bits_per_sample == 0 || self.bits_per_sample > 32 {
+            return Err(CcsdsDecodeError::BitsPerSampleNotSupported(
+                self.bits_per_sample,
+            ));
+        }
+        let valid_block_size = if self.flags & NOT_ENFORCE != 0 {
+            self.block_size != 0 && self.block_size.is_multiple_of(2)
+        } else {
+            matches!(self.block_size, 8 | 16 | 32 | 64)
+        };
+        if !valid_block_size {
+            return Err(CcsdsDecodeError::BlockSizeNotSupported(self.block_size));
+        }
+        if self.rsi == 0 || self.rsi > 4096 {
+            return Err(CcsdsDecodeError::ReferenceSampleIntervalNotSupported(
+                self.rsi,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the length of option identifiers in bits.
+    fn id_len(&self) -> usize {
+        match self.bits_per_sample {
+            1..=2 if self.flags & RESTRICTED != 0 => 1,
+            3..=4 if self.flags & RESTRICTED != 0 => 2,
+            1..=8 => 3,
+            9..=16 => 4,
+            _ => 5,
+        }
+    }
+}
+
+/// Decodes `num_samples` samples from `input`.
+pub(crate) fn decode(
+    input: &[u8],
+    param: &AecParam,
+    num_samples: usize,
+) -> Result<Vec<i64>, CcsdsDecodeError> {
+    param.validate()?;
+
+    let nbit = usize::from(param.bits_per_sample);
+    let block_size = usize::from(param.block_size);
+    let rsi = usize::from(param.rsi);
+    let id_len = param.id_len();
+    let id_uncompressed = (1 << id_len) - 1;
+    let preprocess = param.flags & DATA_PREPROCESS != 0;
+
+    let mut reader = BitReader::new(input);
+    let mut out = Vec::with_capacity(num_samples + block_size * SEGMENT_SIZE);
+
+    while out.len() < num_samples {
+        let rsi_start = out.len();
+        let mut num_blocks = 0;
+        while num_blocks < rsi && out.len() < num_samples {
+            // The first block of a reference sample interval starts with a
+            // reference sample when preprocessing is applied.
+            let with_ref = preprocess && num_blocks == 0;
+            let id = read(&mut reader, id_len)?;
+
+            if id == 0 {
+                let second_extension = read(&mut reader, 1)? == 1;
+                if with_ref {
+                    out.push(read(&mut reader, nbit)?);
+                }
+                if second_extension {
+                    decode_second_extension(&mut reader, &mut out, block_size, with_ref)?;
+                    num_blocks += 1;
+                } else {
+                    let zero_blocks = match read_fs(&mut reader)? + 1 {
+                        ROS => (rsi - num_blocks).min(SEGMENT_SIZE - num_blocks % SEGMENT_SIZE),
+                        n if n > ROS => n - 1,
+                        n => n,
+                    };
+                    if zero_blocks > rsi - num_blocks {
+                        return Err(CcsdsDecodeError::LengthMismatch);
+                    }
+                    let len = zero_blocks * block_size - usize::from(with_ref);
+                    out.resize(out.len() + len, 0);
+                    num_blocks += zero_blocks;
+                }
+            } else if id == id_uncompressed {
+                for _ in 0..block_size {
+                    out.push(read(&mut reader, nbit)?);
+                }
+                num_blocks += 1;
+            } else {
+                let k = (id - 1) as usize;
+                if with_ref {
+                    out.push(read(&mut reader, nbit)?);
+                }
+                let start = out.len();
+                for _ in start..(start + block_size - usize::from(with_ref)) {
+                    out.push(read_fs(&mut reader)? as i64);
+                }
+                for value in out[start..].iter_mut() {
+                    *value = *value << k | read(&mut reader, k)?;
+                }
+                num_blocks += 1;
+            }
+        }
+
+        if param.flags & PAD_RSI != 0 {
+            reader.align();
+        }
+        postprocess(&mut out[rsi_start..], param, preprocess);
+    }
+
+    out.truncate(num_samples);
+    Ok(out)
+}
+
+fn read(reader: &mut BitReader, nbit: usize) -> Result<i64, CcsdsDecodeError> {
+    reader
+        .read(nbit)
+        .map(i64::from)
+        .ok_or(CcsdsDecodeError::LengthMismatch)
+}
+
+/// Reads a fundamental sequence codeword, i.e. zeros terminated by a one,
+/// and returns the number of zeros.
+fn read_fs(reader: &mut BitReader) -> Result<usize, CcsdsDecodeError> {
+    let mut count = 0;
+    while read(reader, 1)? == 0 {
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Decodes a block coded with the second-extension option, where each
+/// codeword represents a pair of samples.  If the block has a reference
+/// sample, the first pair consists of the reference sample slot, which is
+/// coded as zero, and the following sample.
+fn decode_second_extension(
+    reader: &mut BitReader,
+    out: &mut Vec<i64>,
+    block_size: usize,
+    with_ref: bool,
+) -> Result<(), CcsdsDecodeError> {
+    let mut i = usize::from(with_ref);
+    while i < block_size {
+        let m = read_fs(reader)?;
+        let mut sum = 0;
+        while (sum + 1) * (sum + 2) / 2 <= m {
+            sum += 1;
+        }
+        let second = (m - sum * (sum + 1) / 2) as i64;
+        if i % 2 == 0 {
+            out.push(sum as i64 - second);
+            i += 1;
+        }
+        out.push(second);
+        i += 1;
+    }
+    Ok(())
+}
+
+/// Restores samples of a reference sample interval in place.  With
+/// preprocessing, the first sample is the reference sample and the others
+/// are mapped prediction residuals of the unit-delay predictor.
+fn postprocess(samples: &mut [i64], param: &AecParam, preprocess: bool) {
+    let nbit = u32::from(param.bits_per_sample);
+    let signed = param.flags & DATA_SIGNED != 0;
+    let (min, max) = if signed {
+        (-(1_i64 << (nbit - 1)), (1_i64 << (nbit - 1)) - 1)
+    } else {
+        (0, (1_i64 << nbit) - 1)
+    };
+    let sign_extend = |value: i64| {
+        if signed && value > max {
+            value - (1_i64 << nbit)
+        } else {
+            value
+        }
+    };
+
+    if !preprocess {
+        for value in samples.iter_mut() {
+            *value = sign_extend(*value);
+        }
+        return;
+    }
+
+    let mut samples = samples.iter_mut();
+    let mut x = match samples.next() {
+        Some(reference) => {
+            *reference = sign_extend(*reference);
+            *reference
+        }
+        None => return,
+    };
+    for value in samples {
+        let mapped = *value;
+        let theta = (x - min).min(max - x);
+        let residual = if mapped <= 2 * theta {
+            if mapped % 2 == 0 {
+                mapped / 2
+            } else {
+                -(mapped + 1) / 2
+            }
+        } else if x - min <= max - x {
+            mapped - theta
+        } else {
+            theta - mapped
+        };
+        x += residual;
+        *value = x;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct BitWriter {
+        bits: Vec<bool>,
+    }
+
+    impl BitWriter {
+        fn new() -> Self {
+            Self { bits: Vec::new() }
+        }
+
+        fn write(&mut self, value: u32, nbit: usize) -> &mut Self {
+            for i in (0..nbit).rev() {
+                self.bits.push(value >> i & 1 == 1);
+            }
+            self
+        }
+
+        fn write_fs(&mut self, count: usize) -> &mut Self {
+            for _ in 0..count {
+                self.bits.push(false);
+            }
+            self.bits.push(true);
+            self
+        }
+
+        fn align(&mut self) -> &mut Self {
+            while !self.bits.len().is_multiple_of(8) {
+                self.bits.push(false);
+            }
+            self
+        }
+
+        fn into_bytes(mut self) -> Vec<u8> {
+            self.align();
+            self.bits
+                .chunks(8)
+                .map(|bits| bits.iter().fold(0, |acc, bit| acc << 1 | u8::from(*bit)))
+                .collect()
+        }
+    }
+
+    fn param(block_size: u8, rsi: u16, flags: u8) -> AecParam {
+        AecParam {
+            bits_per_sample: 8,
+            block_size,
+            rsi,
+            flags,
+        }
+    }
+
+    #[test]
+    fn uncompressed_blocks_without_preprocessing() {
+        let mut writer = BitWriter::new();
+        writer.write(0b111, 3);
+        for value in 0..8 {
+            writer.write(value * 30, 8);
+        }
+        let input = writer.into_bytes();
+
+        assert_eq!(
+            decode(&input, &param(8, 1, 0), 6),
+            Ok(vec![0, 30, 60, 90, 120, 150])
+        );
+    }
+
+    #[test]
+    fn split_samples_with_reference_sample() {
+        // samples 100, 101, 99, 99, 103, 100, 100, 100 are mapped to the
+        // residuals 2, 3, 0, 8, 5, 0, 0, which are coded with k = 1
+        let mut writer = BitWriter::new();
+        writer.write(2, 3).write(100, 8);
+        for mapped in [2, 3, 0, 8, 5, 0, 0].iter() {
+            writer.write_fs(mapped >> 1);
+        }
+        for mapped in [2_u32, 3, 0, 8, 5, 0, 0].iter() {
+            writer.write(mapped & 1, 1);
+        }
+        let input = writer.into_bytes();
+
+        assert_eq!(
+            decode(&input, &param(8, 1, DATA_PREPROCESS), 8),
+            Ok(vec![100, 101, 99, 99, 103, 100, 100, 100])
+        );
+    }
+
+    #[test]
+    fn residuals_exceeding_distance_to_limits() {
+        // samples 2, 7 and 255, 250, where the residuals 5 and -5 exceed
+        // the distance from the predicted values to the limits
+        let mut writer = BitWriter::new();
+        writer.write(0b111, 3).write(2, 8).write(7, 8);
+        writer.write(0b111, 3).write(255, 8).write(5, 8);
+        let input = writer.into_bytes();
+
+        assert_eq!(
+            decode(&input, &param(2, 1, DATA_PREPROCESS | NOT_ENFORCE), 4),
+            Ok(vec![2, 7, 255, 250])
+        );
+    }
+
+    #[test]
+    fn second_extension_with_and_without_reference_sample() {
+        // The block with a reference sample codes pairs (0, 1), (2, 0),
+        // (1, 1) and (0, 0), and the next block codes pairs (3, 0),
+        // (0, 2), (0, 0) and (1, 0).
+        let mut writer = BitWriter::new();
+        writer.write(0, 3).write(1, 1).write(50, 8);
+        for m in [2, 3, 4, 0].iter() {
+            writer.write_fs(*m);
+        }
+        writer.write(0, 3).write(1, 1);
+        for m in [6, 5, 0, 1].iter() {
+            writer.write_fs(*m);
+        }
+        let input = writer.into_bytes();
+
+        assert_eq!(
+            decode(&input, &param(8, 2, DATA_PREPROCESS), 16),
+            Ok(vec![
+                50, 49, 50, 50, 49, 48, 48, 48, //
+                46, 46, 46, 47, 47, 47, 46, 46,
+            ])
+        );
+    }
+
+    #[test]
+    fn zero_blocks_up_to_remainder_of_segment() {
+        // An RSI of 70 blocks starts with a zero block run of 3 blocks
+        // with a reference sample, an uncompressed block, a run of 6
+        // blocks, a run to the end of the 64-block segment and a run to the
+        // end of the RSI.
+        let mut writer = BitWriter::new();
+        writer.write(0, 3).write(0, 1).write(7, 8).write_fs(2);
+        writer.write(0b111, 3);
+        for _ in 0..8 {
+            writer.write(2, 8);
+        }
+        writer.write(0, 3).write(0, 1).write_fs(6);
+        writer.write(0, 3).write(0, 1).write_fs(4);
+        writer.write(0, 3).write(0, 1).write_fs(4);
+        let input = writer.into_bytes();
+
+        let mut expected = vec![7; 24];
+        expected.extend(8..=15);
+        expected.extend_from_slice(&[15; (70 - 4) * 8]);
+        assert_eq!(
+            decode(&input, &param(8, 70, DATA_PREPROCESS), 70 * 8),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn reference_sample_intervals_padded_to_octet_boundaries() {
+        let mut writer = BitWriter::new();
+        writer.write(1, 3).write(10, 8);
+        for _ in 0..7 {
+            writer.write_fs(1);
+        }
+        writer.align();
+        writer.write(1, 3).write(20, 8);
+        for _ in 0..7 {
+            writer.write_fs(2);
+        }
+        let input = writer.into_bytes();
+
+        assert_eq!(
+            decode(&input, &param(8, 1, DATA_PREPROCESS | PAD_RSI), 12),
+            Ok(vec![10, 9, 8, 7, 6, 5, 4, 3, 20, 21, 22, 23])
+        );
+    }
+
+    #[test]
+    fn signed_samples() {
+        let mut writer = BitWriter::new();
+        writer.write(0b111, 3);
+        for value in [0xff_u32, 0x80, 0x7f, 0, 1, 2, 3, 4].iter() {
+            writer.write(*value, 8);
+        }
+        let input = writer.into_bytes();
+
+        assert_eq!(
+            decode(&input, &param(8, 1, DATA_SIGNED), 3),
+            Ok(vec![-1, -128, 127])
+        );
+        assert_eq!(
+            decode(&input, &param(8, 1, DATA_SIGNED | DATA_PREPROCESS), 3),
+            Ok(vec![-1, 63, -1])
+        );
+    }
+
+    #[test]
+    fn unsupported_parameters_and_short_input() {
+        let param = |bits_per_sample, block_size, rsi| AecParam {
+            bits_per_sample,
+            block_size,
+            rsi,
+            flags: DATA_PREPROCESS,
+        };
+        assert_eq!(
+            decode(&[], &param(33, 8, 1), 1),
+            Err(CcsdsDecodeError::BitsPerSampleNotSupported(33))
+        );
+        assert_eq!(
+            decode(&[], &param(8, 12, 1), 1),
+            Err(CcsdsDecodeError::BlockSizeNotSupported(12))
+        );
+        let odd_block_size = AecParam {
+            flags: DATA_PREPROCESS | NOT_ENFORCE,
+            ..param(8, 3, 1)
+        };
+        assert_eq!(
+            decode(&[], &odd_block_size, 1),
+            Err(CcsdsDecodeError::BlockSizeNotSupported(3))
+        );
+        assert_eq!(
+            decode(&[], &param(8, 8, 0), 1),
+            Err(CcsdsDecodeError::ReferenceSampleIntervalNotSupported(0))
+        );
+        assert_eq!(
+            decode(&[0xff], &param(8, 8, 1), 1),
+            Err(CcsdsDecodeError::LengthMismatch)
+        );
+    }
+}
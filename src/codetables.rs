@@ -4,11 +4,10 @@ pub struct LookupResult(Result<&'static &'static str, ConversionError>);
 
 impl Display for LookupResult {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let s = match &self.0 {
-            Ok(s) => format!("{}", s),
-            Err(e) => format!("{}", e),
-        };
-        write!(f, "{}", s)
+        match &self.0 {
+            Ok(s) => write!(f, "{}", s),
+            Err(e) => write!(f, "{}", e),
+        }
     }
 }
 
@@ -26,7 +25,7 @@ impl Display for ConversionError {
 }
 
 /// Implements "Code Table 1.0: GRIB Master Tables Version Number"
-pub const CODE_TABLE_1_0: &'static [&'static str] = &[
+pub const CODE_TABLE_1_0: &[&str] = &[
     "Experimental",
     "Version implemented on 7 November 2001",
     "Version implemented on 4 November 2003",
@@ -35,12 +34,12 @@ pub const CODE_TABLE_1_0: &'static [&'static str] = &[
 ];
 
 /// Implements "Code Table 1.1: GRIB Local Tables Version Number"
-pub const CODE_TABLE_1_1: &'static [&'static str] = &[
+pub const CODE_TABLE_1_1: &[&str] = &[
     "Local tables not used. Only table entries and templates from the current Master table are valid.",
 ];
 
 /// Implements "Code Table 1.2: Significance of Reference Time"
-pub const CODE_TABLE_1_2: &'static [&'static str] = &[
+pub const CODE_TABLE_1_2: &[&str] = &[
     "Analysis",
     "Start of forecast",
     "Verifying time of forecast",
@@ -48,7 +47,7 @@ pub const CODE_TABLE_1_2: &'static [&'static str] = &[
 ];
 
 /// Implements "Code Table 1.3: Production status of data"
-pub const CODE_TABLE_1_3: &'static [&'static str] = &[
+pub const CODE_TABLE_1_3: &[&str] = &[
     "Operational products",
     "Operational test products",
     "Research products",
@@ -56,7 +55,7 @@ pub const CODE_TABLE_1_3: &'static [&'static str] = &[
 ];
 
 /// Implements "Code Table 1.4: Type of data"
-pub const CODE_TABLE_1_4: &'static [&'static str] = &[
+pub const CODE_TABLE_1_4: &[&str] = &[
     "Analysis products",
     "Forecast products",
     "Analysis and forecast products",
@@ -67,9 +66,283 @@ pub const CODE_TABLE_1_4: &'static [&'static str] = &[
     "Processed radar observations",
 ];
 
+/// Implements a part of "Code Table 4.2: Parameter number by product
+/// discipline and parameter category" as a list of tuples of
+/// discipline, parameter category, parameter number, name and unit
+pub const CODE_TABLE_4_2: &[(u8, u8, u8, &str, &str)] = &[
+    (0, 0, 0, "Temperature", "K"),
+    (0, 0, 2, "Potential temperature", "K"),
+    (0, 0, 4, "Maximum temperature", "K"),
+    (0, 0, 5, "Minimum temperature", "K"),
+    (0, 0, 6, "Dew point temperature", "K"),
+    (0, 1, 0, "Specific humidity", "kg kg-1"),
+    (0, 1, 1, "Relative humidity", "%"),
+    (0, 1, 3, "Precipitable water", "kg m-2"),
+    (0, 1, 7, "Precipitation rate", "kg m-2 s-1"),
+    (0, 1, 8, "Total precipitation", "kg m-2"),
+    (0, 1, 11, "Snow depth", "m"),
+    (
+        0,
+        1,
+        13,
+        "Water equivalent of accumulated snow depth",
+        "kg m-2",
+    ),
+    (0, 1, 52, "Total precipitation rate", "kg m-2 s-1"),
+    (
+        0,
+        2,
+        0,
+        "Wind direction (from which blowing)",
+        "degree true",
+    ),
+    (0, 2, 1, "Wind speed", "m s-1"),
+    (0, 2, 2, "u-component of wind", "m s-1"),
+    (0, 2, 3, "v-component of wind", "m s-1"),
+    (0, 2, 8, "Vertical velocity (pressure)", "Pa s-1"),
+    (0, 2, 9, "Vertical velocity (geometric)", "m s-1"),
+    (0, 2, 10, "Absolute vorticity", "s-1"),
+    (0, 2, 12, "Relative vorticity", "s-1"),
+    (0, 2, 13, "Relative divergence", "s-1"),
+    (0, 2, 22, "Wind speed (gust)", "m s-1"),
+    (0, 3, 0, "Pressure", "Pa"),
+    (0, 3, 1, "Pressure reduced to MSL", "Pa"),
+    (0, 3, 4, "Geopotential", "m2 s-2"),
+    (0, 3, 5, "Geopotential height", "gpm"),
+    (0, 3, 25, "Natural logarithm of pressure in Pa", "Numeric"),
+    (0, 6, 1, "Total cloud cover", "%"),
+    (0, 7, 6, "Convective available potential energy", "J kg-1"),
+    (0, 19, 0, "Visibility", "m"),
+    (0, 19, 2, "Thunderstorm probability", "%"),
+    (
+        10,
+        0,
+        3,
+        "Significant height of combined wind waves and swell",
+        "m",
+    ),
+];
+
+/// Looks up the name and the unit of a parameter in Code Table 4.2.
+pub fn lookup_parameter(
+    discipline: u8,
+    category: u8,
+    number: u8,
+) -> Option<(&'static str, &'static str)> {
+    CODE_TABLE_4_2
+        .iter()
+        .find(|(d, c, n, _, _)| (*d, *c, *n) == (discipline, category, number))
+        .map(|(_, _, _, name, unit)| (*name, *unit))
+}
+
+/// Implements a part of "Code Table 4.5: Fixed surface types and units"
+/// as a list of tuples of code, name and unit, where the unit is empty
+/// for surfaces without a value
+pub const CODE_TABLE_4_5: &[(u8, &str, &str)] = &[
+    (1, "Ground or water surface", ""),
+    (2, "Cloud base level", ""),
+    (3, "Level of cloud tops", ""),
+    (4, "Level of 0 degree C isotherm", ""),
+    (
+        5,
+        "Level of adiabatic condensation lifted from the surface",
+        "",
+    ),
+    (6, "Maximum wind level", ""),
+    (7, "Tropopause", ""),
+    (8, "Nominal top of the atmosphere", ""),
+    (9, "Sea bottom", ""),
+    (10, "Entire atmosphere", ""),
+    (11, "Cumulonimbus base", "m"),
+    (12, "Cumulonimbus top", "m"),
+    (20, "Isothermal level", "K"),
+    (100, "Isobaric surface", "Pa"),
+    (101, "Mean sea level", ""),
+    (102, "Specific altitude above mean sea level", "m"),
+    (103, "Specified height level above ground", "m"),
+    (104, "Sigma level", "Numeric"),
+    (105, "Hybrid level", "Numeric"),
+    (106, "Depth below land surface", "m"),
+    (107, "Isentropic (theta) level", "K"),
+    (
+        108,
+        "Level at specified pressure difference from ground to level",
+        "Pa",
+    ),
+    (109, "Potential vorticity surface", "K m2 kg-1 s-1"),
+    (111, "Eta level", "Numeric"),
+    (113, "Logarithmic hybrid level", "Numeric"),
+    (114, "Snow level", "Numeric"),
+    (117, "Mixed layer depth", "m"),
+    (118, "Hybrid height level", "Numeric"),
+    (119, "Hybrid pressure level", "Numeric"),
+    (150, "Generalized vertical height coordinate", "Numeric"),
+    (151, "Soil level", "Numeric"),
+    (160, "Depth below sea level", "m"),
+    (161, "Depth below water surface", "m"),
+    (162, "Lake or river bottom", ""),
+    (163, "Bottom of sediment layer", ""),
+    (164, "Bottom of thermally active sediment layer", ""),
+    (
+        165,
+        "Bottom of sediment layer penetrated by thermal wave",
+        "",
+    ),
+    (166, "Mixing layer", ""),
+    (167, "Bottom of root zone", ""),
+    (174, "Top surface of ice on sea, lake or river", ""),
+    (
+        175,
+        "Top surface of ice, under snow cover, on sea, lake or river",
+        "",
+    ),
+    (
+        176,
+        "Bottom surface (underside) ice on sea, lake or river",
+        "",
+    ),
+    (177, "Deep soil (of indefinite depth)", ""),
+];
+
+/// Looks up the name and the unit of a fixed surface type in Code Table
+/// 4.5.
+pub fn lookup_surface(code: u8) -> Option<(&'static str, &'static str)> {
+    CODE_TABLE_4_5
+        .iter()
+        .find(|(c, _, _)| *c == code)
+        .map(|(_, name, unit)| (*name, *unit))
+}
+
+/// Implements a part of "Code Table 4.230: Atmospheric chemical
+/// constituent type" as a list of tuples of code and name
+///
+/// Aerosol types in Code Table 4.233 share the codes with this table.
+pub const CODE_TABLE_4_230: &[(u16, &str)] = &[
+    (0, "Ozone O3"),
+    (1, "Water vapour H2O"),
+    (2, "Methane CH4"),
+    (3, "Carbon dioxide CO2"),
+    (4, "Carbon monoxide CO"),
+    (5, "Nitrogen dioxide NO2"),
+    (6, "Nitrous oxide N2O"),
+    (7, "Formaldehyde HCHO"),
+    (8, "Sulphur dioxide SO2"),
+    (9, "Ammonia NH3"),
+    (10, "Ammonium cation NH4+"),
+    (11, "Nitrogen monoxide NO"),
+    (12, "Atomic oxygen O"),
+    (13, "Nitrate radical NO3"),
+    (14, "Hydroperoxyl radical HO2"),
+    (15, "Dinitrogen pentoxide N2O5"),
+    (16, "Nitrous acid HONO"),
+    (17, "Nitric acid HNO3"),
+    (18, "Peroxynitric acid HO2NO2"),
+    (19, "Hydrogen peroxide H2O2"),
+    (20, "Molecular hydrogen H"),
+    (21, "Atomic nitrogen N"),
+    (22, "Sulphate anion SO4 2-"),
+    (23, "Atomic radon Rn"),
+    (24, "Mercury vapour Hg(0)"),
+    (25, "Mercury(II) cation Hg2+"),
+    (26, "Atomic chlorine Cl"),
+    (27, "Chlorine monoxide ClO"),
+    (28, "Dichlorine peroxide Cl2O2"),
+    (29, "Hypochlorous acid HClO"),
+    (30, "Chlorine nitrate ClONO2"),
+    (31, "Chlorine dioxide ClO2"),
+    (32, "Atomic bromine Br"),
+    (33, "Bromine monoxide BrO"),
+    (34, "Bromine chloride BrCl"),
+    (35, "Hydrogen bromide HBr"),
+    (36, "Hypobromous acid HBrO"),
+    (37, "Bromine nitrate BrONO2"),
+    (38, "Oxygen O2"),
+    (10000, "Hydroxyl radical OH"),
+    (10001, "Methyl peroxy radical CH3O2"),
+    (10002, "Methyl hydroperoxide CH3O2H"),
+    (10004, "Methanol CH3OH"),
+    (10005, "Formic acid CH3OOH"),
+    (10006, "Hydrogen cyanide HCN"),
+    (10007, "Aceto nitrile CH3CN"),
+    (10008, "Ethane C2H6"),
+    (10009, "Ethene (= Ethylene) C2H4"),
+    (10010, "Ethyne (= Acetylene) C2H2"),
+    (10011, "Ethanol C2H5OH"),
+    (10012, "Acetic acid C2H5OOH"),
+    (10013, "Peroxyacetyl nitrate CH3C(O)OONO2"),
+    (10014, "Propane C3H8"),
+    (10015, "Propene C3H6"),
+    (10016, "Butanes C4H10"),
+    (10017, "Isoprene C5H10"),
+    (10018, "Alpha pinene C10H16"),
+    (10019, "Beta pinene C10H16"),
+    (10020, "Limonene C10H16"),
+    (10021, "Benzene C6H6"),
+    (10022, "Toluene C7H8"),
+    (10023, "Xylene C8H10"),
+    (62000, "Total aerosol"),
+    (62001, "Dust dry"),
+    (62002, "Water in ambient"),
+    (62003, "Ammonium dry"),
+    (62004, "Nitrate dry"),
+    (62005, "Nitric acid trihydrate"),
+    (62006, "Sulphate dry"),
+    (62007, "Mercury dry"),
+    (62008, "Sea salt dry"),
+    (62009, "Black carbon dry"),
+    (62010, "Particulate organic matter dry"),
+    (62011, "Primary particulate organic matter dry"),
+    (62012, "Secondary particulate organic matter dry"),
+    (62013, "Black carbon hydrophilic dry"),
+    (62014, "Black carbon hydrophobic dry"),
+    (62015, "Particulate organic matter hydrophilic dry"),
+    (62016, "Particulate organic matter hydrophobic dry"),
+    (62017, "Nitrate hydrophilic dry"),
+    (62018, "Nitrate hydrophobic dry"),
+    (62020, "Smoke - high absorption"),
+    (62021, "Smoke - low absorption"),
+    (62022, "Aerosol - high absorption"),
+    (62023, "Aerosol - low absorption"),
+    (62025, "Volcanic ash"),
+];
+
+/// Looks up the name of an atmospheric chemical constituent or an
+/// aerosol type in Code Table 4.230.
+pub fn lookup_constituent(code: u16) -> Option<&'static str> {
+    CODE_TABLE_4_230
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| *name)
+}
+
 pub fn lookup_table(table: &'static [&'static str], code: u8) -> LookupResult {
     let result = table
         .get(code as usize)
         .ok_or(ConversionError::Unimplemented(code));
     LookupResult(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_parameter_implemented() {
+        assert_eq!(
+            lookup_parameter(0, 1, 8),
+            Some(("Total precipitation", "kg m-2"))
+        );
+    }
+
+    #[test]
+    fn lookup_parameter_unimplemented() {
+        assert_eq!(lookup_parameter(0, 193, 0), None);
+    }
+
+    #[test]
+    fn lookup_constituent_of_chemical_and_aerosol() {
+        assert_eq!(lookup_constituent(0), Some("Ozone O3"));
+        assert_eq!(lookup_constituent(62001), Some("Dust dry"));
+        assert_eq!(lookup_constituent(65535), None);
+    }
+}
@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryInto;
 use std::fmt::{self, Display, Formatter};
 use std::io::{Read, Seek};
 use std::result::Result;
@@ -8,8 +9,16 @@ use std::result::Result;
 use crate::codetables::{
     lookup_table, CODE_TABLE_1_0, CODE_TABLE_1_1, CODE_TABLE_1_2, CODE_TABLE_1_3, CODE_TABLE_1_4,
 };
-use crate::decoder::{self, DecodeError};
+use crate::decoder::{
+    self, DecodeBuffers, DecodeError, IntegerValue, MissingValue, SpectralPackingDecodeError,
+};
+use crate::products::{
+    self, Aerosol, AnalysisOrForecast, ChemicalConstituent, DerivedForecast, EnsembleMember,
+    ForecastTime, HybridCoefficients, ProbabilityForecast, SatelliteProduct, StatisticalProcessing,
+};
 use crate::reader::{Grib2Read, ParseError, SeekableGrib2Reader};
+use crate::spectral::{self, SynthesisGrid};
+use crate::utils::read_as;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SectionInfo {
@@ -40,6 +49,7 @@ impl Display for SectionInfo {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SectionBody {
+    Section0(Indicator),
     Section1(Identification),
     Section2,
     Section3(GridDefinition),
@@ -60,6 +70,14 @@ impl SectionBody {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Indicator {
+    /// Discipline - GRIB Master Table Number (see Code Table 0.0)
+    pub discipline: u8,
+    /// Total length of GRIB message in octets (including Section 0)
+    pub total_length: u64,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Identification {
     /// Identification of originating/generating centre (see Common Code Table C-1)
@@ -113,6 +131,67 @@ pub struct GridDefinition {
     pub num_points: u32,
     /// Grid Definition Template Number
     pub grid_tmpl_num: u16,
+    /// Octets from octet 15 to the end of the section, which contain
+    /// the Grid Definition Template
+    pub templated: Box<[u8]>,
+}
+
+impl GridDefinition {
+    /// Returns truncation parameters of spherical harmonic
+    /// coefficients if the grid is defined with Grid Definition
+    /// Template 3.50.
+    pub fn spherical_harmonics(&self) -> Option<SphericalHarmonics> {
+        if self.grid_tmpl_num != 50 {
+            return None;
+        }
+
+        let buf = &self.templated;
+        if buf.len() < 14 {
+            return None;
+        }
+
+        Some(SphericalHarmonics {
+            j: read_as!(u32, buf, 0),
+            k: read_as!(u32, buf, 4),
+            m: read_as!(u32, buf, 8),
+            repr_type: buf[12],
+            repr_mode: buf[13],
+        })
+    }
+}
+
+/// Truncation of spherical harmonic coefficients defined in Grid
+/// Definition Template 3.50
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SphericalHarmonics {
+    /// J - pentagonal resolution parameter
+    pub j: u32,
+    /// K - pentagonal resolution parameter
+    pub k: u32,
+    /// M - pentagonal resolution parameter
+    pub m: u32,
+    /// Representation type indicating the method used to define the
+    /// norm (see Code Table 3.6)
+    pub repr_type: u8,
+    /// Representation mode (see Code Table 3.7)
+    pub repr_mode: u8,
+}
+
+impl SphericalHarmonics {
+    /// Returns the largest total wavenumber `n` for the zonal
+    /// wavenumber `m`.
+    pub fn max_n(&self, m: u32) -> u32 {
+        std::cmp::min(m + self.j, self.k)
+    }
+
+    /// Returns the number of complex coefficients.  Each coefficient
+    /// is stored as a pair of real and imaginary parts, so the
+    /// number of data points is twice this number.
+    pub fn num_coefficients(&self) -> usize {
+        (0..=self.m)
+            .map(|m| (self.max_n(m) + 1).saturating_sub(m) as usize)
+            .sum()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -121,6 +200,154 @@ pub struct ProdDefinition {
     pub num_coordinates: u16,
     /// Product Definition Template Number
     pub prod_tmpl_num: u16,
+    /// Octets from octet 10 to the end of the section, which contain
+    /// the Product Definition Template and the coordinate values
+    pub templated: Box<[u8]>,
+}
+
+impl ProdDefinition {
+    /// Returns the contents of Product Definition Template 4.0 if the
+    /// template used is 4.0 or one of the templates starting with the
+    /// same octets as 4.0.  For templates 4.40 to 4.48, octets
+    /// describing the constituent or the aerosol are skipped.
+    pub fn analysis_or_forecast(&self) -> Option<AnalysisOrForecast> {
+        match self.prod_tmpl_num {
+            0..=15 => AnalysisOrForecast::from_bytes(&self.templated),
+            40..=43 => AnalysisOrForecast::from_bytes_with_insertion(&self.templated, 2),
+            44..=47 => AnalysisOrForecast::from_bytes_with_insertion(&self.templated, 13),
+            48 => AnalysisOrForecast::from_bytes_with_insertion(&self.templated, 24),
+            _ => None,
+        }
+    }
+
+    /// Returns ensemble member information if the template used is
+    /// 4.1 or 4.11, or one of its variants for atmospheric chemical
+    /// constituents and aerosols.
+    pub fn ensemble_member(&self) -> Option<EnsembleMember> {
+        let start = match self.prod_tmpl_num {
+            1 | 11 => 25,  // octet 35
+            41 | 43 => 27, // octet 37
+            45 | 47 => 38, // octet 48
+            _ => return None,
+        };
+        EnsembleMember::from_bytes(self.templated.get(start..)?)
+    }
+
+    /// Returns derived forecast information if the template used is 4.2
+    /// or 4.12.
+    pub fn derived_forecast(&self) -> Option<DerivedForecast> {
+        match self.prod_tmpl_num {
+            2 | 12 => DerivedForecast::from_bytes(self.templated.get(25..)?), // octet 35
+            _ => None,
+        }
+    }
+
+    /// Returns probability information if the template used is 4.5 or
+    /// 4.9.
+    pub fn probability_forecast(&self) -> Option<ProbabilityForecast> {
+        match self.prod_tmpl_num {
+            5 | 9 => ProbabilityForecast::from_bytes(self.templated.get(25..)?), // octet 35
+            _ => None,
+        }
+    }
+
+    /// Returns the atmospheric chemical constituent if the template used
+    /// is one of 4.40 to 4.43.
+    pub fn chemical_constituent(&self) -> Option<ChemicalConstituent> {
+        match self.prod_tmpl_num {
+            40..=43 => ChemicalConstituent::from_bytes(self.templated.get(2..)?), // octet 12
+            _ => None,
+        }
+    }
+
+    /// Returns aerosol information if the template used is one of 4.44
+    /// to 4.48.
+    pub fn aerosol(&self) -> Option<Aerosol> {
+        match self.prod_tmpl_num {
+            44..=47 => Aerosol::from_bytes(self.templated.get(2..)?, false), // octet 12
+            48 => Aerosol::from_bytes(self.templated.get(2..)?, true),
+            _ => None,
+        }
+    }
+
+    /// Returns satellite product information including spectral bands
+    /// if the template used is 4.31 or 4.32.
+    pub fn satellite_product(&self) -> Option<SatelliteProduct> {
+        match self.prod_tmpl_num {
+            31 => SatelliteProduct::from_bytes_4_31(&self.templated),
+            32 => SatelliteProduct::from_bytes_4_32(&self.templated),
+            _ => None,
+        }
+    }
+
+    /// Returns the list of vertical coordinate values stored after the
+    /// template, or `None` if the section is too short to contain
+    /// them.
+    pub fn coordinates(&self) -> Option<Box<[f32]>> {
+        let len = usize::from(self.num_coordinates) * 4;
+        let start = self.templated.len().checked_sub(len)?;
+        let coordinates = self.templated[start..]
+            .chunks_exact(4)
+            .map(|buf| read_as!(f32, buf, 0))
+            .collect();
+        Some(coordinates)
+    }
+
+    /// Returns coefficients of hybrid levels contained in the list of
+    /// vertical coordinate values.
+    pub fn hybrid_coefficients(&self) -> Option<HybridCoefficients> {
+        HybridCoefficients::from_coordinates(&self.coordinates()?)
+    }
+
+    /// Returns statistical processing information if the template used
+    /// is one for statistically processed values such as 4.8.
+    pub fn statistical_processing(&self) -> Option<StatisticalProcessing> {
+        let start = match self.prod_tmpl_num {
+            8 => 25,  // octet 35
+            9 => 38,  // octet 48
+            10 => 26, // octet 36
+            11 => 28, // octet 38
+            12 => 27, // octet 37
+            42 => 27, // octet 37
+            43 => 30, // octet 40
+            46 => 38, // octet 48
+            47 => 41, // octet 51
+            _ => return None,
+        };
+        StatisticalProcessing::from_bytes(self.templated.get(start..)?)
+    }
+
+    /// Returns the forecast time if the template used contains it.
+    pub fn forecast_time(&self) -> Option<ForecastTime> {
+        match self.analysis_or_forecast() {
+            Some(product) => Some(product.forecast_time),
+            None => self.satellite_product()?.forecast_time,
+        }
+    }
+
+    /// Returns the valid time of the data for the reference time
+    /// `ref_time` of Section 1.  For statistically processed data, the
+    /// end of the overall time interval is returned.
+    pub fn valid_time(&self, ref_time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.statistical_processing() {
+            Some(statistics) => Some(statistics.end_time),
+            None => self.forecast_time()?.valid_time(ref_time),
+        }
+    }
+
+    /// Computes the end of the overall time interval of statistically
+    /// processed data from the reference time `ref_time`, the forecast
+    /// time, which is the start of the interval, and the length of the
+    /// outermost time range.
+    pub fn interval_end(&self, ref_time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let outermost = self
+            .statistical_processing()?
+            .time_ranges
+            .into_iter()
+            .next()?;
+        let start = self.forecast_time()?.valid_time(ref_time)?;
+        products::shift_time(start, outermost.length_unit, outermost.length.into())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -162,6 +389,10 @@ pub struct Grib2<R> {
     reader: RefCell<R>,
     sections: Box<[SectionInfo]>,
     submessages: Box<[SubMessage]>,
+    predefined_bitmaps: HashMap<u8, Box<[u8]>>,
+    /// Buffers reused by `get_values_into`
+    bitmap_buf: RefCell<Vec<u8>>,
+    decode_buffers: RefCell<DecodeBuffers>,
 }
 
 impl<R: Grib2Read> Grib2<R> {
@@ -171,7 +402,10 @@ impl<R: Grib2Read> Grib2<R> {
         Ok(Self {
             reader: RefCell::new(r),
             sections: sects,
-            submessages: submessages,
+            submessages,
+            predefined_bitmaps: HashMap::new(),
+            bitmap_buf: RefCell::new(Vec::new()),
+            decode_buffers: RefCell::new(DecodeBuffers::default()),
         })
     }
 
@@ -182,29 +416,272 @@ impl<R: Grib2Read> Grib2<R> {
         Grib2::<SeekableGrib2Reader<SR>>::read(r)
     }
 
+    // Kept as `&Box` to leave the public API unchanged.
+    #[allow(clippy::borrowed_box)]
     pub fn submessages(&self) -> &Box<[SubMessage]> {
         &self.submessages
     }
 
     /// Decodes grid values of a surface specified by the index `i`.
     pub fn get_values(&self, i: usize) -> Result<Box<[f32]>, GribError> {
-        let (sect5, sect6, sect7) = self
-            .submessages
+        let (sect3, sect5, sect7) = self.data_sections(i)?;
+        let bitmap = self.bitmap(i)?;
+        let reader = self.reader.borrow_mut();
+        let values = decoder::dispatch(sect3, sect5, bitmap.as_deref(), sect7, reader)?;
+        Ok(values)
+    }
+
+    /// Decodes grid values of a surface specified by the index `i` into
+    /// `out`, which must have as many elements as [`Grib2::get_values`]
+    /// would return.
+    ///
+    /// Buffers used for decoding are kept and reused.  Decoding fields of
+    /// the same size repeatedly does not allocate only if both of the
+    /// following hold:
+    ///
+    /// * the reader is a [`SeekableGrib2Reader`], or overrides
+    ///   [`Grib2Read::read_sect_body_bytes_into`], and
+    /// * the data use simple packing (Templates 5.0 and 5.61), complex
+    ///   packing (Templates 5.2 and 5.3) or IEEE floating-point data
+    ///   (Template 5.4).
+    ///
+    /// Decoding into buffers is not yet implemented for matrix values
+    /// (Template 5.1), JPEG 2000 (5.40), PNG (5.41), CCSDS (5.42),
+    /// spherical harmonics (5.50 and 5.51) and run-length encoding
+    /// (5.200).  Values of these templates are decoded as with
+    /// [`Grib2::get_values`] and copied into `out`, which allocates.
+    pub fn get_values_into(&self, i: usize, out: &mut [f32]) -> Result<(), GribError> {
+        let (sect3, sect5, sect7) = self.data_sections(i)?;
+        let mut bitmap = self.bitmap_buf.borrow_mut();
+        let has_bitmap = self.bitmap_into(i, &mut bitmap)?;
+        let bitmap = has_bitmap.then_some(&bitmap[..]);
+        let reader = self.reader.borrow_mut();
+        let mut buffers = self.decode_buffers.borrow_mut();
+        decoder::dispatch_into(sect3, sect5, bitmap, sect7, reader, &mut buffers, out)
+    }
+
+    /// Decodes grid values of a surface specified by the index `i`,
+    /// representing missing values as specified by `missing` instead of
+    /// NaN.
+    ///
+    /// NaN values stored in IEEE floating-point data (Template 5.4) are
+    /// indistinguishable from missing values and are replaced as well.
+    pub fn get_values_with_missing(
+        &self,
+        i: usize,
+        missing: MissingValue,
+    ) -> Result<Box<[f32]>, GribError> {
+        let mut values = self.get_values(i)?;
+        missing.apply(&mut values);
+        Ok(values)
+    }
+
+    /// Decodes grid values of a surface specified by the index `i`, with
+    /// `None` for missing values.
+    pub fn get_optional_values(&self, i: usize) -> Result<Box<[Option<f32>]>, GribError> {
+        let values = self.get_values(i)?;
+        let values = values
+            .iter()
+            .map(|value| if value.is_nan() { None } else { Some(*value) })
+            .collect();
+        Ok(values)
+    }
+
+    /// Decodes grid values of a surface specified by the index `i` in
+    /// double precision.
+    ///
+    /// Packed values are scaled with the reference value and the binary and
+    /// decimal scale factors in double precision, and 64-bit IEEE
+    /// floating-point data are not rounded to `f32`.
+    pub fn get_values_f64(&self, i: usize) -> Result<Box<[f64]>, GribError> {
+        let (sect3, sect5, sect7) = self.data_sections(i)?;
+        let bitmap = self.bitmap(i)?;
+        let reader = self.reader.borrow_mut();
+        let values = decoder::dispatch(sect3, sect5, bitmap.as_deref(), sect7, reader)?;
+        Ok(values)
+    }
+
+    /// Decodes grid values of a surface of an integer field, such as
+    /// precipitation type or land cover, specified by the index `i`.
+    ///
+    /// Values are returned as `i32` or `u32`, with `None` for missing
+    /// values.  The original field values must be integers (Code Table 5.1
+    /// value type 1).
+    pub fn get_integer_values<T: IntegerValue>(
+        &self,
+        i: usize,
+    ) -> Result<Box<[Option<T>]>, GribError> {
+        let (sect3, sect5, sect7) = self.data_sections(i)?;
+        let bitmap = self.bitmap(i)?;
+        let reader = self.reader.borrow_mut();
+        let values = decoder::dispatch_integer(sect3, sect5, bitmap.as_deref(), sect7, reader)?;
+        Ok(values)
+    }
+
+    /// Returns Sections 3, 5 and 7 of the submessage specified by the
+    /// index `i`.
+    fn data_sections(
+        &self,
+        i: usize,
+    ) -> Result<(&SectionInfo, &SectionInfo, &SectionInfo), GribError> {
+        self.submessages
             .get(i)
             .and_then(|submsg| {
                 Some((
+                    submsg.section3.and_then(|i| self.sections.get(i))?,
                     submsg.section5.and_then(|i| self.sections.get(i))?,
-                    submsg.section6.and_then(|i| self.sections.get(i))?,
                     submsg.section7.and_then(|i| self.sections.get(i))?,
                 ))
             })
+            .ok_or(GribError::InternalDataError)
+    }
+
+    /// Registers a predefined bit map applied to submessages whose bit-map
+    /// indicator in Section 6 is `indicator` (1 to 253).
+    ///
+    /// Predefined bit maps are not contained in GRIB2 data and are defined
+    /// by originating centres.  `bitmap` has an element for each grid
+    /// point, which is `true` if the point has a value.
+    pub fn set_predefined_bitmap(&mut self, indicator: u8, bitmap: &[bool]) {
+        let mut packed = vec![0_u8; bitmap.len().div_ceil(8)];
+        for (pos, _) in bitmap.iter().enumerate().filter(|(_, bit)| **bit) {
+            packed[pos / 8] |= 0x80 >> (pos % 8);
+        }
+        self.predefined_bitmaps
+            .insert(indicator, packed.into_boxed_slice());
+    }
+
+    /// Returns the bit map applied to the submessage specified by the index
+    /// `i`, or `None` if all grid points have values.
+    fn bitmap(&self, i: usize) -> Result<Option<Box<[u8]>>, GribError> {
+        let mut buf = Vec::new();
+        let has_bitmap = self.bitmap_into(i, &mut buf)?;
+        Ok(has_bitmap.then(|| buf.into_boxed_slice()))
+    }
+
+    /// Reads the bit map applied to the submessage specified by the index
+    /// `i` into `buf`, replacing its contents, and returns `false` if all
+    /// grid points have values.
+    fn bitmap_into(&self, i: usize, buf: &mut Vec<u8>) -> Result<bool, GribError> {
+        let index = self
+            .submessages
+            .get(i)
+            .and_then(|submsg| submsg.section6)
             .ok_or(GribError::InternalDataError)?;
+        let (sect6, indicator) = match bitmap_indicator(&self.sections[index]) {
+            // A bit map previously defined in the same message applies.
+            Some(254) => self.sections[..index]
+                .iter()
+                .rev()
+                .take_while(|sect| sect.num != 0)
+                .find_map(|sect| match bitmap_indicator(sect) {
+                    Some(indicator) if indicator < 254 => Some((sect, indicator)),
+                    _ => None,
+                })
+                .ok_or(DecodeError::PreviousBitMapNotFound)?,
+            Some(indicator) => (&self.sections[index], indicator),
+            None => return Err(GribError::InternalDataError),
+        };
 
-        let reader = self.reader.borrow_mut();
-        let values = decoder::dispatch(sect5, sect6, sect7, reader)?;
+        match indicator {
+            // The bit map starts at octet 7 of Section 6.
+            0 => {
+                self.reader
+                    .borrow_mut()
+                    .read_sect_body_bytes_into(sect6, buf)?;
+                buf.drain(..1);
+                Ok(true)
+            }
+            255 => Ok(false),
+            _ => {
+                let bitmap = self
+                    .predefined_bitmaps
+                    .get(&indicator)
+                    .ok_or(DecodeError::PredefinedBitMapNotFound(indicator))?;
+                buf.clear();
+                buf.extend_from_slice(bitmap);
+                Ok(true)
+            }
+        }
+    }
+
+    /// Returns the Indicator Section.
+    pub fn indicator(&self) -> Option<&Indicator> {
+        match self.sections.first() {
+            Some(SectionInfo {
+                body: Some(SectionBody::Section0(body)),
+                ..
+            }) => Some(body),
+            _ => None,
+        }
+    }
+
+    /// Returns the Identification Section.
+    pub fn identification(&self) -> Option<&Identification> {
+        match self.sections.get(1) {
+            Some(SectionInfo {
+                body: Some(SectionBody::Section1(body)),
+                ..
+            }) => Some(body),
+            _ => None,
+        }
+    }
+
+    /// Returns the valid time of a surface specified by the index `i`.
+    pub fn valid_time(&self, i: usize) -> Option<DateTime<Utc>> {
+        let ref_time = self.identification()?.ref_time;
+        self.prod_def(i)?.valid_time(ref_time)
+    }
+
+    /// Returns the Grid Definition Section of a surface specified by
+    /// the index `i`.
+    pub fn grid_def(&self, i: usize) -> Option<&GridDefinition> {
+        let submsg = self.submessages.get(i)?;
+        match self.sections.get(submsg.section3?) {
+            Some(SectionInfo {
+                body: Some(SectionBody::Section3(body)),
+                ..
+            }) => Some(body),
+            _ => None,
+        }
+    }
+
+    /// Returns the Product Definition Section of a surface specified by
+    /// the index `i`.
+    pub fn prod_def(&self, i: usize) -> Option<&ProdDefinition> {
+        let submsg = self.submessages.get(i)?;
+        match self.sections.get(submsg.section4?) {
+            Some(SectionInfo {
+                body: Some(SectionBody::Section4(body)),
+                ..
+            }) => Some(body),
+            _ => None,
+        }
+    }
+
+    /// Decodes spherical harmonic coefficients of a surface specified
+    /// by the index `i` and evaluates them onto `grid`.
+    pub fn get_values_on_grid(
+        &self,
+        i: usize,
+        grid: &SynthesisGrid,
+    ) -> Result<Box<[f32]>, GribError> {
+        let truncation = self
+            .grid_def(i)
+            .ok_or(GribError::InternalDataError)?
+            .spherical_harmonics()
+            .ok_or(DecodeError::SpectralPackingDecodeError(
+                SpectralPackingDecodeError::NotSpectralGrid,
+            ))?;
+
+        let coefficients = self.get_values(i)?;
+        let values = spectral::synthesize(&coefficients, &truncation, grid)
+            .map_err(DecodeError::SpectralPackingDecodeError)?;
         Ok(values)
     }
 
+    // Kept as `&Box` to leave the public API unchanged.
+    #[allow(clippy::borrowed_box)]
     pub fn sections(&self) -> &Box<[SectionInfo]> {
         &self.sections
     }
@@ -216,21 +693,23 @@ impl<R: Grib2Read> Grib2<R> {
 
 impl<R: Grib2Read> Display for Grib2<R> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let err = "No information available".to_string();
-        let s = match self.sections.get(1) {
-            Some(SectionInfo {
-                body: Some(SectionBody::Section1(body)),
-                ..
-            }) => format!("{}", body),
-            _ => err,
-        };
-        write!(f, "{}", s)
+        match self.identification() {
+            Some(body) => write!(f, "{}", body),
+            None => write!(f, "No information available"),
+        }
+    }
+}
+
+fn bitmap_indicator(sect: &SectionInfo) -> Option<u8> {
+    match &sect.body {
+        Some(SectionBody::Section6(body)) => Some(body.bitmap_indicator),
+        _ => None,
     }
 }
 
 /// Validates the section order of sections and split them into a
 /// vector of section groups.
-fn get_submessages(sects: &Box<[SectionInfo]>) -> Result<Box<[SubMessage]>, ValidationError> {
+fn get_submessages(sects: &[SectionInfo]) -> Result<Box<[SubMessage]>, ValidationError> {
     let mut iter = sects.iter().enumerate();
     let mut starts = Vec::new();
     let mut i2_default = None;
@@ -295,7 +774,7 @@ fn get_submessages(sects: &Box<[SectionInfo]>) -> Result<Box<[SubMessage]>, Vali
                 })
             }
             Some((i, SectionInfo { num: 4, .. })) => {
-                if i3_default == None {
+                if i3_default.is_none() {
                     return Err(ValidationError::NoGridDefinition(i));
                 }
                 let (i, _) = sect.unwrap();
@@ -312,7 +791,7 @@ fn get_submessages(sects: &Box<[SectionInfo]>) -> Result<Box<[SubMessage]>, Vali
                 })
             }
             Some((i, SectionInfo { num: 8, .. })) => {
-                if i3_default == None {
+                if i3_default.is_none() {
                     return Err(ValidationError::NoGridDefinition(i));
                 }
                 if i < sects.len() - 1 {
@@ -333,7 +812,7 @@ fn get_submessages(sects: &Box<[SectionInfo]>) -> Result<Box<[SubMessage]>, Vali
     Ok(starts.into_boxed_slice())
 }
 
-fn get_templates(sects: &Box<[SectionInfo]>) -> Vec<TemplateInfo> {
+fn get_templates(sects: &[SectionInfo]) -> Vec<TemplateInfo> {
     let uniq: HashSet<_> = sects.iter().filter_map(|s| s.get_tmpl_code()).collect();
     let mut vec: Vec<_> = uniq.into_iter().collect();
     vec.sort_unstable();
@@ -398,6 +877,61 @@ impl Display for ValidationError {
 mod tests {
     use super::*;
 
+    use crate::products::{
+        EnsembleType, FixedSurface, LeadTime, StatisticalInterval, StatisticalProcess,
+    };
+    use chrono::offset::TimeZone;
+    use std::fs::File;
+    use std::io::{BufReader, Cursor};
+    use std::path::Path;
+    use xz2::bufread::XzDecoder;
+
+    fn read_xz<P: AsRef<Path>>(path: P) -> Vec<u8> {
+        let f = File::open(path).unwrap();
+        let f = BufReader::new(f);
+        let mut f = XzDecoder::new(f);
+        let mut buf = Vec::new();
+        f.read_to_end(&mut buf).unwrap();
+        buf
+    }
+
+    fn grib_from<P: AsRef<Path>>(path: P) -> Grib2<SeekableGrib2Reader<Cursor<Vec<u8>>>> {
+        let f = Cursor::new(read_xz(path));
+        Grib2::<SeekableGrib2Reader<Cursor<Vec<u8>>>>::read_with_seekable(f).unwrap()
+    }
+
+    fn tornado_nowcast() -> Grib2<SeekableGrib2Reader<Cursor<Vec<u8>>>> {
+        grib_from(
+            "testdata/Z__C_RJTD_20160822020000_NOWC_GPV_Ggis10km_Pphw10_FH0000-0100_grib2.bin.xz",
+        )
+    }
+
+    fn kousa_bitmap_bytes() -> Vec<u8> {
+        read_xz("testdata/gen/kousa-bitmap.grib2.xz")
+    }
+
+    fn kousa_bitmap() -> Grib2<SeekableGrib2Reader<Cursor<Vec<u8>>>> {
+        grib_from("testdata/gen/kousa-bitmap.grib2.xz")
+    }
+
+    fn kousa() -> Grib2<SeekableGrib2Reader<Cursor<Vec<u8>>>> {
+        grib_from(
+            "testdata/Z__C_RJTD_20170221120000_MSG_GPV_Gll0p5deg_Pys_B20170221120000_F2017022115-2017022212_grib2.bin.xz",
+        )
+    }
+
+    fn kousa_repacked(name: &str) -> Grib2<SeekableGrib2Reader<Cursor<Vec<u8>>>> {
+        grib_from(format!("testdata/gen/kousa-{}.grib2.xz", name))
+    }
+
+    fn kousa_ieee_bytes(precision: usize) -> Vec<u8> {
+        read_xz(format!("testdata/gen/kousa-ieee{}.grib2.xz", precision))
+    }
+
+    fn kousa_ieee(precision: usize) -> Grib2<SeekableGrib2Reader<Cursor<Vec<u8>>>> {
+        grib_from(format!("testdata/gen/kousa-ieee{}.grib2.xz", precision))
+    }
+
     macro_rules! sect_placeholder {
         ($num:expr) => {{
             SectionInfo {
@@ -697,6 +1231,297 @@ mod tests {
         );
     }
 
+    #[test]
+    fn analysis_or_forecast_of_submessage() {
+        let grib2 = tornado_nowcast();
+
+        assert_eq!(
+            grib2.prod_def(1).and_then(|p| p.analysis_or_forecast()),
+            Some(AnalysisOrForecast {
+                parameter_category: 193,
+                parameter_number: 0,
+                generating_process: 2,
+                background_process_id: 153,
+                forecast_process_id: 255,
+                cutoff_hours: 0,
+                cutoff_minutes: 0,
+                forecast_time: ForecastTime { unit: 0, value: 10 },
+                first_surface: FixedSurface {
+                    surface_type: 1,
+                    scale_factor: -127,
+                    scaled_value: 0xffffffff,
+                },
+                second_surface: FixedSurface {
+                    surface_type: 255,
+                    scale_factor: -127,
+                    scaled_value: 0xffffffff,
+                },
+            })
+        );
+        assert_eq!(grib2.prod_def(7), None);
+    }
+
+    #[test]
+    fn statistical_processing_of_template_4_8() {
+        let prod_def = ProdDefinition {
+            num_coordinates: 0,
+            prod_tmpl_num: 8,
+            templated: vec![
+                0x01, 0x08, 0x02, 0x00, 0x60, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x01,
+                0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x07, 0xe4, 0x0c,
+                0x01, 0x0c, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x01, 0x00, 0x00,
+                0x00, 0x06, 0xff, 0x00, 0x00, 0x00, 0x00,
+            ]
+            .into_boxed_slice(),
+        };
+
+        assert_eq!(
+            prod_def.statistical_processing().and_then(|s| s.interval()),
+            Some(StatisticalInterval {
+                start: Utc.with_ymd_and_hms(2020, 12, 1, 6, 0, 0).unwrap(),
+                end: Utc.with_ymd_and_hms(2020, 12, 1, 12, 0, 0).unwrap(),
+                statistic: StatisticalProcess::Accumulation,
+            })
+        );
+        assert_eq!(
+            prod_def.analysis_or_forecast().map(|a| a.forecast_time),
+            Some(ForecastTime { unit: 1, value: 6 })
+        );
+
+        let ref_time = Utc.with_ymd_and_hms(2020, 12, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            prod_def.valid_time(ref_time),
+            Some(Utc.with_ymd_and_hms(2020, 12, 1, 12, 0, 0).unwrap())
+        );
+        assert_eq!(
+            prod_def.interval_end(ref_time),
+            Some(Utc.with_ymd_and_hms(2020, 12, 1, 12, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn valid_time_of_submessage() {
+        let grib2 = tornado_nowcast();
+
+        assert_eq!(
+            grib2.valid_time(1),
+            Some(Utc.with_ymd_and_hms(2016, 8, 22, 2, 10, 0).unwrap())
+        );
+        assert_eq!(
+            grib2
+                .prod_def(6)
+                .and_then(|p| p.forecast_time()?.lead_time()),
+            Some(LeadTime::Duration(chrono::Duration::try_hours(1).unwrap()))
+        );
+        assert_eq!(
+            grib2.prod_def(6).and_then(|p| p.interval_end(Utc::now())),
+            None
+        );
+        assert_eq!(grib2.valid_time(7), None);
+    }
+
+    #[test]
+    fn ensemble_member_of_template_4_11() {
+        let mut templated = vec![0; 25];
+        templated.extend_from_slice(&[0x02, 0x05, 0x15]);
+        templated.extend_from_slice(&[
+            0x07, 0xe4, 0x0c, 0x01, 0x0c, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02,
+            0x01, 0x00, 0x00, 0x00, 0x06, 0xff, 0x00, 0x00, 0x00, 0x00,
+        ]);
+        let prod_def = ProdDefinition {
+            num_coordinates: 0,
+            prod_tmpl_num: 11,
+            templated: templated.into_boxed_slice(),
+        };
+
+        assert_eq!(
+            prod_def.ensemble_member(),
+            Some(EnsembleMember {
+                ensemble_type: EnsembleType::NegativelyPerturbed,
+                perturbation_number: 5,
+                num_members: 21,
+            })
+        );
+        assert_eq!(prod_def.derived_forecast(), None);
+        assert_eq!(
+            prod_def.statistical_processing().map(|s| s.end_time),
+            Some(Utc.with_ymd_and_hms(2020, 12, 1, 12, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn chemical_constituent_of_template_4_43() {
+        let mut templated = vec![0x14, 0x00, 0x00, 0x04];
+        templated.extend_from_slice(&[
+            0x02, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x01, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        ]);
+        templated.extend_from_slice(&[0x03, 0x01, 0x33]);
+        templated.extend_from_slice(&[
+            0x07, 0xe4, 0x0c, 0x01, 0x0c, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02,
+            0x01, 0x00, 0x00, 0x00, 0x06, 0xff, 0x00, 0x00, 0x00, 0x00,
+        ]);
+        let prod_def = ProdDefinition {
+            num_coordinates: 0,
+            prod_tmpl_num: 43,
+            templated: templated.into_boxed_slice(),
+        };
+
+        let product = prod_def.analysis_or_forecast().unwrap();
+        assert_eq!(
+            (product.parameter_category, product.parameter_number),
+            (20, 0)
+        );
+        assert_eq!(product.generating_process, 2);
+        assert_eq!(product.forecast_time, ForecastTime { unit: 1, value: 3 });
+        assert_eq!(product.first_surface.surface_type, 1);
+        assert_eq!(
+            prod_def.chemical_constituent(),
+            Some(ChemicalConstituent {
+                constituent_type: 4
+            })
+        );
+        assert_eq!(prod_def.aerosol(), None);
+        assert_eq!(
+            prod_def.ensemble_member().map(|e| e.perturbation_number),
+            Some(1)
+        );
+        assert_eq!(
+            prod_def.statistical_processing().map(|s| s.end_time),
+            Some(Utc.with_ymd_and_hms(2020, 12, 1, 12, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn aerosol_of_template_4_48() {
+        let mut templated = vec![0x14, 0x66, 0xf2, 0x31];
+        templated.extend_from_slice(&[
+            0x02, 0x06, 0x00, 0x00, 0x00, 0x02, 0x06, 0x00, 0x00, 0x00, 0x14,
+        ]);
+        templated.extend_from_slice(&[
+            0x0b, 0x09, 0x00, 0x00, 0x02, 0x26, 0x09, 0x00, 0x00, 0x02, 0x26,
+        ]);
+        templated.extend_from_slice(&[
+            0x02, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x01, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        ]);
+        let prod_def = ProdDefinition {
+            num_coordinates: 0,
+            prod_tmpl_num: 48,
+            templated: templated.into_boxed_slice(),
+        };
+
+        let product = prod_def.analysis_or_forecast().unwrap();
+        assert_eq!(
+            (product.parameter_category, product.parameter_number),
+            (20, 102)
+        );
+        assert_eq!(product.forecast_time, ForecastTime { unit: 1, value: 3 });
+        let aerosol = prod_def.aerosol().unwrap();
+        assert_eq!(aerosol.aerosol_type, 62001);
+        assert_eq!(aerosol.name(), Some("Dust dry"));
+        assert_eq!(aerosol.size.interval_type, 2);
+        assert_eq!(aerosol.size.first_limit.value(), Some(2e-6));
+        assert_eq!(aerosol.size.second_limit.value(), Some(2e-5));
+        let wavelength = aerosol.wavelength.unwrap();
+        assert_eq!(wavelength.interval_type, 11);
+        assert_eq!(wavelength.first_limit.value(), Some(5.5e-7));
+        assert_eq!(prod_def.chemical_constituent(), None);
+        assert_eq!(prod_def.statistical_processing(), None);
+    }
+
+    #[test]
+    fn satellite_product_of_template_4_31() {
+        let mut templated = vec![0x00, 0x04, 0x08, 0x00, 0x02];
+        templated.extend_from_slice(&[
+            0x01, 0x4d, 0x00, 0x04, 0x00, 0xcf, 0x02, 0x00, 0x01, 0xd4, 0xc0,
+        ]);
+        templated.extend_from_slice(&[
+            0x01, 0x4d, 0x00, 0x04, 0x00, 0xcf, 0x81, 0x00, 0x00, 0x01, 0xf4,
+        ]);
+        let prod_def = ProdDefinition {
+            num_coordinates: 0,
+            prod_tmpl_num: 31,
+            templated: templated.into_boxed_slice(),
+        };
+
+        let product = prod_def.satellite_product().unwrap();
+        assert_eq!(
+            (product.parameter_category, product.parameter_number),
+            (0, 4)
+        );
+        assert_eq!(product.generating_process, 8);
+        assert_eq!(product.forecast_time, None);
+        assert_eq!(product.bands.len(), 2);
+        assert_eq!(
+            product.bands[0].to_string(),
+            "satellite series 333, satellite number 4, instrument type 207, \
+central wave number 1200 m-1"
+        );
+        assert_eq!(product.bands[1].central_wave_number.value(), Some(5000.));
+        assert_eq!(prod_def.analysis_or_forecast(), None);
+    }
+
+    #[test]
+    fn satellite_product_with_truncated_band_loop() {
+        let prod_def = ProdDefinition {
+            num_coordinates: 0,
+            prod_tmpl_num: 32,
+            templated: vec![0; 20].into_boxed_slice(),
+        };
+        let mut templated = vec![0; 14];
+        templated[13] = 1;
+        templated.extend_from_slice(&[0; 10]);
+        let truncated = ProdDefinition {
+            templated: templated.into_boxed_slice(),
+            ..prod_def.clone()
+        };
+
+        assert_eq!(
+            prod_def.satellite_product().map(|p| p.bands),
+            Some(Vec::new())
+        );
+        assert_eq!(truncated.satellite_product(), None);
+    }
+
+    #[test]
+    fn coordinates_after_template() {
+        let mut templated = vec![0; 25];
+        for value in [0_f32, 2000., 0., 0., 0.25, 1.].iter() {
+            templated.extend_from_slice(&value.to_be_bytes());
+        }
+        let prod_def = ProdDefinition {
+            num_coordinates: 6,
+            prod_tmpl_num: 0,
+            templated: templated.into_boxed_slice(),
+        };
+
+        assert_eq!(
+            prod_def.coordinates(),
+            Some(vec![0., 2000., 0., 0., 0.25, 1.].into_boxed_slice())
+        );
+        assert_eq!(
+            prod_def.hybrid_coefficients(),
+            Some(HybridCoefficients {
+                a: vec![0., 2000., 0.].into_boxed_slice(),
+                b: vec![0., 0.25, 1.].into_boxed_slice(),
+            })
+        );
+        assert!(prod_def.analysis_or_forecast().is_some());
+    }
+
+    #[test]
+    fn coordinates_of_too_short_section() {
+        let prod_def = ProdDefinition {
+            num_coordinates: 8,
+            prod_tmpl_num: 0,
+            templated: vec![0; 25].into_boxed_slice(),
+        };
+
+        assert_eq!(prod_def.coordinates(), None);
+        assert_eq!(prod_def.hybrid_coefficients(), None);
+    }
+
     #[test]
     fn get_tmpl_code_normal() {
         let sect = SectionInfo {
@@ -712,6 +1537,39 @@ mod tests {
         assert_eq!(sect.get_tmpl_code(), Some(TemplateInfo(5, 200)));
     }
 
+    #[test]
+    fn spherical_harmonics_from_grid_template_3_50() {
+        let grid_def = GridDefinition {
+            num_points: 2 * 214 * 213 / 2,
+            grid_tmpl_num: 50,
+            templated: vec![
+                0x00, 0x00, 0x00, 0xd4, 0x00, 0x00, 0x00, 0xd4, 0x00, 0x00, 0x00, 0xd4, 0x01, 0x01,
+            ]
+            .into_boxed_slice(),
+        };
+        let truncation = SphericalHarmonics {
+            j: 212,
+            k: 212,
+            m: 212,
+            repr_type: 1,
+            repr_mode: 1,
+        };
+
+        assert_eq!(grid_def.spherical_harmonics(), Some(truncation.clone()));
+        assert_eq!(truncation.num_coefficients(), 214 * 213 / 2);
+    }
+
+    #[test]
+    fn spherical_harmonics_from_other_templates() {
+        let grid_def = GridDefinition {
+            num_points: 0,
+            grid_tmpl_num: 0,
+            templated: vec![0; 58].into_boxed_slice(),
+        };
+
+        assert_eq!(grid_def.spherical_harmonics(), None);
+    }
+
     #[test]
     fn get_templates_normal() {
         let sects = vec![
@@ -724,6 +1582,7 @@ mod tests {
                 body: Some(SectionBody::Section3(GridDefinition {
                     num_points: 0,
                     grid_tmpl_num: 0,
+                    templated: Vec::new().into_boxed_slice(),
                 })),
             },
             SectionInfo {
@@ -733,6 +1592,7 @@ mod tests {
                 body: Some(SectionBody::Section4(ProdDefinition {
                     num_coordinates: 0,
                     prod_tmpl_num: 0,
+                    templated: Vec::new().into_boxed_slice(),
                 })),
             },
             SectionInfo {
@@ -753,6 +1613,7 @@ mod tests {
                 body: Some(SectionBody::Section3(GridDefinition {
                     num_points: 0,
                     grid_tmpl_num: 1,
+                    templated: Vec::new().into_boxed_slice(),
                 })),
             },
             SectionInfo {
@@ -762,6 +1623,7 @@ mod tests {
                 body: Some(SectionBody::Section4(ProdDefinition {
                     num_coordinates: 0,
                     prod_tmpl_num: 0,
+                    templated: Vec::new().into_boxed_slice(),
                 })),
             },
             SectionInfo {
@@ -789,4 +1651,207 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn integer_values_of_non_integer_field() {
+        let grib = tornado_nowcast();
+        assert_eq!(
+            grib.get_integer_values::<i32>(0),
+            Err(GribError::DecodeError(
+                DecodeError::OriginalFieldValueTypeNotInteger
+            ))
+        );
+    }
+
+    /// Returns the position of the bit-map indicator of the second field of
+    /// the kousa bit-map data, which is 254.
+    fn reused_bitmap_indicator_pos(buf: &[u8]) -> usize {
+        buf.windows(6)
+            .position(|octets| octets == [0, 0, 0, 6, 6, 254])
+            .unwrap()
+            + 5
+    }
+
+    #[test]
+    fn values_with_predefined_bitmap() {
+        let mut buf = kousa_bitmap_bytes();
+        let pos = reused_bitmap_indicator_pos(&buf);
+        buf[pos] = 3;
+        let mut grib =
+            Grib2::<SeekableGrib2Reader<Cursor<Vec<u8>>>>::read_with_seekable(Cursor::new(buf))
+                .unwrap();
+
+        assert_eq!(
+            grib.get_values(1),
+            Err(GribError::DecodeError(
+                DecodeError::PredefinedBitMapNotFound(3)
+            ))
+        );
+
+        let bitmap = (0..4941).map(|i| i % 5 != 0).collect::<Vec<_>>();
+        grib.set_predefined_bitmap(3, &bitmap);
+        let expected = grib.get_values(0).unwrap();
+        let actual = grib.get_values(1).unwrap();
+        assert_eq!(actual.len(), 4941);
+        assert!(actual.iter().step_by(5).all(|value| value.is_nan()));
+        assert_eq!(
+            actual
+                .iter()
+                .map(|value| value.to_bits())
+                .collect::<Vec<_>>(),
+            expected
+                .iter()
+                .map(|value| value.to_bits())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn values_with_no_previous_bitmap() {
+        let mut buf = kousa_bitmap_bytes();
+        // The bit map of the first field, whose Section 6 is 624 octets
+        // long, is replaced with an indicator showing that no bit map
+        // applies.
+        let pos = buf
+            .windows(6)
+            .position(|octets| octets == [0, 0, 2, 112, 6, 0])
+            .unwrap()
+            + 5;
+        buf[pos] = 255;
+        let grib =
+            Grib2::<SeekableGrib2Reader<Cursor<Vec<u8>>>>::read_with_seekable(Cursor::new(buf))
+                .unwrap();
+
+        assert_eq!(
+            grib.get_values(1),
+            Err(GribError::DecodeError(DecodeError::PreviousBitMapNotFound))
+        );
+    }
+
+    #[test]
+    fn values_with_missing_value_representations() {
+        let grib = kousa_bitmap();
+        let values = grib.get_values(0).unwrap();
+
+        let actual = grib
+            .get_values_with_missing(0, MissingValue::Value(9.999e20))
+            .unwrap();
+        assert_eq!(actual[0], 9.999e20);
+        assert_eq!(actual[1], values[1]);
+
+        let actual = grib.get_optional_values(0).unwrap();
+        assert_eq!(actual.len(), values.len());
+        assert_eq!(actual[0], None);
+        assert_eq!(actual[1], Some(values[1]));
+    }
+
+    #[test]
+    fn nan_of_ieee_float_data_treated_as_missing() {
+        // Replaces the first value of Section 7 with NaN.
+        let mut buf = kousa_ieee_bytes(32);
+        let sect7 = kousa_ieee(32)
+            .sections()
+            .iter()
+            .find(|sect| sect.num == 7)
+            .unwrap()
+            .offset;
+        buf[(sect7 + 5)..(sect7 + 9)].copy_from_slice(&f32::NAN.to_be_bytes());
+        let grib =
+            Grib2::<SeekableGrib2Reader<Cursor<Vec<u8>>>>::read_with_seekable(Cursor::new(buf))
+                .unwrap();
+
+        let values = grib
+            .get_values_with_missing(0, MissingValue::Value(9.999e20))
+            .unwrap();
+        assert_eq!(values[0], 9.999e20);
+        assert_eq!(grib.get_optional_values(0).unwrap()[0], None);
+    }
+
+    #[test]
+    fn packed_values_scaled_in_double_precision() {
+        // The 64-bit IEEE data contain values of the packed data computed
+        // in double precision.
+        let expected = kousa_ieee(64).get_values_f64(0).unwrap();
+
+        for (grib, index) in [
+            (kousa(), 3),
+            (kousa_repacked("complex"), 0),
+            (kousa_repacked("png"), 0),
+            (kousa_repacked("ccsds"), 0),
+        ]
+        .iter()
+        {
+            let actual = grib.get_values_f64(*index).unwrap();
+            assert_eq!(actual, expected);
+
+            // Values scaled in single precision have errors.
+            let values = grib.get_values(*index).unwrap();
+            assert!(values
+                .iter()
+                .zip(expected.iter())
+                .any(|(value, expected)| f64::from(*value) != *expected));
+        }
+    }
+
+    #[test]
+    fn values_decoded_into_buffers() {
+        let gribs = [
+            kousa(),
+            kousa_repacked("complex"),
+            kousa_repacked("complex-diff1"),
+            kousa_repacked("complex-diff2-missing"),
+            kousa_repacked("complex-missing"),
+            kousa_repacked("png"),
+            kousa_ieee(32),
+            kousa_ieee(64),
+            tornado_nowcast(),
+            kousa_bitmap(),
+        ];
+
+        for grib in gribs.iter() {
+            // Buffers are reused across submessages.
+            for i in 0..grib.submessages().len() {
+                let expected = grib.get_values(i).unwrap();
+                let mut actual = vec![0_f32; expected.len()];
+                grib.get_values_into(i, &mut actual).unwrap();
+                assert_eq!(
+                    actual.iter().map(|v| v.to_bits()).collect::<Vec<_>>(),
+                    expected.iter().map(|v| v.to_bits()).collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn values_decoded_into_buffers_of_wrong_length() {
+        for grib in [kousa(), tornado_nowcast()].iter() {
+            let len = grib.get_values(0).unwrap().len();
+            for len in [len - 1, len + 1] {
+                let mut out = vec![0_f32; len];
+                assert_eq!(
+                    grib.get_values_into(0, &mut out),
+                    Err(GribError::DecodeError(DecodeError::OutputLengthMismatch))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn values_of_64_bit_ieee_float_data_decoded_in_double_precision() {
+        let grib64 = kousa_ieee(64);
+        let values = grib64.get_values_f64(0).unwrap();
+        assert_eq!(values.len(), 4941);
+        assert_eq!(values[100], 1.946172574207594e-06);
+
+        // The single-precision values are equal to those of the 32-bit data.
+        let grib32 = kousa_ieee(32);
+        let values32 = grib32.get_values(0).unwrap();
+        assert_eq!(grib64.get_values(0).unwrap(), values32);
+        assert_eq!(values32[100], values[100] as f32);
+        assert_ne!(f64::from(values32[100]), values[100]);
+        assert_eq!(
+            grib32.get_values_f64(0).unwrap(),
+            values32.iter().map(|v| f64::from(*v)).collect()
+        );
+    }
 }
@@ -1,16 +1,37 @@
 use std::cell::RefMut;
 use std::convert::TryInto;
+use std::ops::{Add, Mul, Sub};
 
-use crate::context::{GribError, SectionBody, SectionInfo};
+use crate::ccsds;
+use crate::context::{GribError, SectionBody, SectionInfo, SphericalHarmonics};
 use crate::reader::Grib2Read;
-use crate::utils::GribInt;
+use crate::utils::{read_as, BitReader, GribInt};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum DecodeError {
     TemplateNumberUnsupported,
     BitMapIndicatorUnsupported,
+    BitMapLengthMismatch,
+    /// Bit-map indicator 254 is used but no bit map is defined before in
+    /// the same message.
+    PreviousBitMapNotFound,
+    /// No predefined bit map is registered for the bit-map indicator.
+    PredefinedBitMapNotFound(u8),
     SimplePackingDecodeError(SimplePackingDecodeError),
+    ComplexPackingDecodeError(ComplexPackingDecodeError),
+    SpectralPackingDecodeError(SpectralPackingDecodeError),
     RunLengthEncodingDecodeError(RunLengthEncodingDecodeError),
+    Jpeg2000CodeStreamDecodeError(Jpeg2000CodeStreamDecodeError),
+    PngDecodeError(PngDecodeError),
+    CcsdsDecodeError(CcsdsDecodeError),
+    IeeeFloatDecodeError(IeeeFloatDecodeError),
+    /// Values are requested as integers but the original field values are
+    /// not integers (see Code Table 5.1).
+    OriginalFieldValueTypeNotInteger,
+    IntegerValueOutOfRange,
+    /// The length of the output buffer differs from the number of decoded
+    /// values.
+    OutputLengthMismatch,
 }
 
 impl From<SimplePackingDecodeError> for DecodeError {
@@ -19,16 +40,71 @@ impl From<SimplePackingDecodeError> for DecodeError {
     }
 }
 
+impl From<ComplexPackingDecodeError> for DecodeError {
+    fn from(e: ComplexPackingDecodeError) -> Self {
+        Self::ComplexPackingDecodeError(e)
+    }
+}
+
+impl From<SpectralPackingDecodeError> for DecodeError {
+    fn from(e: SpectralPackingDecodeError) -> Self {
+        Self::SpectralPackingDecodeError(e)
+    }
+}
+
 impl From<RunLengthEncodingDecodeError> for DecodeError {
     fn from(e: RunLengthEncodingDecodeError) -> Self {
         Self::RunLengthEncodingDecodeError(e)
     }
 }
 
+impl From<Jpeg2000CodeStreamDecodeError> for DecodeError {
+    fn from(e: Jpeg2000CodeStreamDecodeError) -> Self {
+        Self::Jpeg2000CodeStreamDecodeError(e)
+    }
+}
+
+impl From<PngDecodeError> for DecodeError {
+    fn from(e: PngDecodeError) -> Self {
+        Self::PngDecodeError(e)
+    }
+}
+
+impl From<CcsdsDecodeError> for DecodeError {
+    fn from(e: CcsdsDecodeError) -> Self {
+        Self::CcsdsDecodeError(e)
+    }
+}
+
+impl From<IeeeFloatDecodeError> for DecodeError {
+    fn from(e: IeeeFloatDecodeError) -> Self {
+        Self::IeeeFloatDecodeError(e)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum SimplePackingDecodeError {
     NotSupported,
     OriginalFieldValueTypeNotSupported,
+    MatrixBitmapsNotSupported,
+    LengthMismatch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ComplexPackingDecodeError {
+    OriginalFieldValueTypeNotSupported,
+    GroupSplittingMethodNotSupported(u8),
+    MissingValueManagementNotSupported(u8),
+    SpatialDifferencingOrderNotSupported(u8),
+    LengthMismatch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SpectralPackingDecodeError {
+    NotSpectralGrid,
+    UnpackedSubsetPrecisionNotSupported(u8),
+    /// Packed coefficients of Template 5.51 cannot be unpacked.
+    SimplePackingDecodeError(SimplePackingDecodeError),
     LengthMismatch,
 }
 
@@ -37,30 +113,72 @@ pub enum RunLengthEncodingDecodeError {
     NotSupported,
     InvalidFirstValue,
     LengthMismatch,
-    InvalidLevelValue(u8),
+    InvalidLevelValue(u16),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Jpeg2000CodeStreamDecodeError {
+    /// The crate is built without the `jpeg2000` feature.
+    NotSupported,
+    OriginalFieldValueTypeNotSupported,
+    CodeStreamDecodeFailed,
+    LengthMismatch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PngDecodeError {
+    OriginalFieldValueTypeNotSupported,
+    BitDepthNotSupported(u8),
+    /// The number of bits per pixel of the image differs from that of
+    /// Section 5.
+    BitDepthMismatch,
+    PngError(String),
+    LengthMismatch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CcsdsDecodeError {
+    OriginalFieldValueTypeNotSupported,
+    BitsPerSampleNotSupported(u8),
+    BlockSizeNotSupported(u8),
+    ReferenceSampleIntervalNotSupported(u16),
+    LengthMismatch,
 }
 
-macro_rules! read_as {
-    ($ty:ty, $buf:ident, $start:expr) => {{
-        let end = $start + std::mem::size_of::<$ty>();
-        <$ty>::from_be_bytes($buf[$start..end].try_into().unwrap())
-    }};
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum IeeeFloatDecodeError {
+    /// Precision other than 32 and 64 bits (see Code Table 5.7)
+    PrecisionNotSupported(u8),
+    LengthMismatch,
 }
 
-pub fn dispatch<R: Grib2Read>(
+/// Decodes values into `f32` or `f64`.
+///
+/// Packed values are restored in the precision of the output type, so
+/// that decoding into `f64` does the reference, binary and decimal
+/// scaling in double precision.
+pub fn dispatch<R: Grib2Read, T: FloatValue>(
+    sect3: &SectionInfo,
     sect5: &SectionInfo,
-    sect6: &SectionInfo,
+    bitmap: Option<&[u8]>,
     sect7: &SectionInfo,
     reader: RefMut<R>,
-) -> Result<Box<[f32]>, GribError> {
+) -> Result<Box<[T]>, GribError> {
     let sect5_body = match &sect5.body {
         Some(SectionBody::Section5(body)) => body,
         _ => return Err(GribError::InternalDataError),
     };
 
     let decoded = match sect5_body.repr_tmpl_num {
-        0 => SimplePackingDecoder::decode(sect5, sect6, sect7, reader)?,
-        200 => RunLengthEncodingDecoder::decode(sect5, sect6, sect7, reader)?,
+        0 | 1 | 61 => SimplePackingDecoder::decode(sect3, sect5, bitmap, sect7, reader)?,
+        2 | 3 => ComplexPackingDecoder::decode(sect3, sect5, bitmap, sect7, reader)?,
+        4 => IeeeFloatDecoder::decode(sect3, sect5, bitmap, sect7, reader)?,
+        40 => Jpeg2000CodeStreamDecoder::decode(sect3, sect5, bitmap, sect7, reader)?,
+        41 => PngDecoder::decode(sect3, sect5, bitmap, sect7, reader)?,
+        42 => CcsdsDecoder::decode(sect3, sect5, bitmap, sect7, reader)?,
+        50 => SpectralSimplePackingDecoder::decode(sect3, sect5, bitmap, sect7, reader)?,
+        51 => SpectralComplexPackingDecoder::decode(sect3, sect5, bitmap, sect7, reader)?,
+        200 => RunLengthEncodingDecoder::decode(sect3, sect5, bitmap, sect7, reader)?,
         _ => {
             return Err(GribError::DecodeError(
                 DecodeError::TemplateNumberUnsupported,
@@ -70,49 +188,332 @@ pub fn dispatch<R: Grib2Read>(
     Ok(decoded)
 }
 
+/// Scratch buffers reused across calls of [`dispatch_into`]
+///
+/// Once the buffers have grown to the sizes needed, decoding fields of the
+/// same size again does not allocate.
+#[derive(Debug, Default)]
+pub struct DecodeBuffers {
+    sect5: Vec<u8>,
+    sect7: Vec<u8>,
+    values: Vec<f32>,
+    groups: Vec<GroupedValue>,
+}
+
+/// Decodes values into `out`, which must have as many elements as
+/// [`dispatch`] would return, using `buffers` for intermediate data.
+///
+/// Simple packing (Templates 5.0 and 5.61), complex packing (Templates 5.2
+/// and 5.3) and IEEE floating-point data (Template 5.4) are decoded without
+/// heap allocation once `buffers` have grown to the sizes needed, provided
+/// that `reader` reads section bodies into buffers without allocating.
+/// Decoding into buffers is not yet implemented for Templates 5.1, 5.40,
+/// 5.41, 5.42, 5.50, 5.51 and 5.200, whose values are decoded with
+/// [`dispatch`] and copied, which allocates.
+pub fn dispatch_into<R: Grib2Read>(
+    sect3: &SectionInfo,
+    sect5: &SectionInfo,
+    bitmap: Option<&[u8]>,
+    sect7: &SectionInfo,
+    mut reader: RefMut<R>,
+    buffers: &mut DecodeBuffers,
+    out: &mut [f32],
+) -> Result<(), GribError> {
+    let (sect3_body, sect5_body) = match (sect3.body.as_ref(), sect5.body.as_ref()) {
+        (Some(SectionBody::Section3(b3)), Some(SectionBody::Section5(b5))) => (b3, b5),
+        _ => return Err(GribError::InternalDataError),
+    };
+    let DecodeBuffers {
+        sect5: sect5_data,
+        sect7: sect7_data,
+        values,
+        groups,
+    } = buffers;
+    let num_values = sect5_body.num_points as usize;
+
+    match sect5_body.repr_tmpl_num {
+        0 | 61 => {
+            reader.read_sect_body_bytes_into(sect5, sect5_data)?;
+            let param = SimplePackingParam::from_bytes(sect5_body.repr_tmpl_num, sect5_data)
+                .map_err(DecodeError::SimplePackingDecodeError)?;
+            reader.read_sect_body_bytes_into(sect7, sect7_data)?;
+            unpack_simple_packing_into(
+                sect7_data,
+                param.nbit,
+                param.ref_val,
+                param.exp,
+                param.dig,
+                Some(num_values),
+                values,
+            )
+            .map_err(DecodeError::SimplePackingDecodeError)?;
+            match param.variant {
+                SimplePackingVariant::Logarithmic(preprocessing) => {
+                    reverse_logarithmic_preprocessing(values, preprocessing)
+                }
+                _ => param.value_type.restore(values),
+            }
+        }
+        2 | 3 => {
+            reader.read_sect_body_bytes_into(sect5, sect5_data)?;
+            let param = ComplexPackingParam::from_bytes(sect5_body.repr_tmpl_num, sect5_data)
+                .map_err(DecodeError::ComplexPackingDecodeError)?;
+            reader.read_sect_body_bytes_into(sect7, sect7_data)?;
+            unpack_complex_packing_into(sect7_data, &param, num_values, groups, values)
+                .map_err(DecodeError::ComplexPackingDecodeError)?;
+        }
+        4 => {
+            reader.read_sect_body_bytes_into(sect5, sect5_data)?;
+            let precision =
+                ieee_float_precision(sect5_data).map_err(DecodeError::IeeeFloatDecodeError)?;
+            reader.read_sect_body_bytes_into(sect7, sect7_data)?;
+            unpack_ieee_float_into(sect7_data, precision, num_values, values)
+                .map_err(DecodeError::IeeeFloatDecodeError)?;
+        }
+        _ => {
+            let decoded = dispatch::<R, f32>(sect3, sect5, bitmap, sect7, reader)?;
+            return copy_values(&decoded, out);
+        }
+    }
+
+    match bitmap {
+        Some(bitmap) => {
+            if out.len() != sect3_body.num_points as usize {
+                return Err(GribError::DecodeError(DecodeError::OutputLengthMismatch));
+            }
+            apply_bitmap_into(values, bitmap, out)?;
+            Ok(())
+        }
+        None => copy_values(values, out),
+    }
+}
+
+fn copy_values(values: &[f32], out: &mut [f32]) -> Result<(), GribError> {
+    if values.len() != out.len() {
+        return Err(GribError::DecodeError(DecodeError::OutputLengthMismatch));
+    }
+    out.copy_from_slice(values);
+    Ok(())
+}
+
+/// Representation of missing values in decoded values
+///
+/// Decoders represent missing values with NaN, so NaN contained in the
+/// data itself, such as in IEEE floating-point data (Template 5.4), is
+/// also treated as missing.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MissingValue {
+    /// NaN, which decoders use for missing values
+    #[default]
+    Nan,
+    /// A sentinel value, such as `9.999e20` used by wgrib
+    Value(f32),
+}
+
+impl MissingValue {
+    /// Replaces missing values, which are NaN, in `values`.
+    pub(crate) fn apply(self, values: &mut [f32]) {
+        if let Self::Value(missing) = self {
+            values
+                .iter_mut()
+                .filter(|value| value.is_nan())
+                .for_each(|value| *value = missing);
+        }
+    }
+}
+
+/// Floating-point types into which values are decoded.
+pub trait FloatValue:
+    Copy + PartialEq + From<f32> + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const NAN: Self;
+
+    fn from_i64(value: i64) -> Self;
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+    fn powi(self, n: i32) -> Self;
+    fn exp(self) -> Self;
+    fn round(self) -> Self;
+    fn is_nan(self) -> bool;
+}
+
+macro_rules! impl_float_value {
+    ($($ty:ty,)*) => {$(
+        impl FloatValue for $ty {
+            const NAN: Self = <$ty>::NAN;
+
+            fn from_i64(value: i64) -> Self {
+                value as $ty
+            }
+
+            fn from_f64(value: f64) -> Self {
+                value as $ty
+            }
+
+            fn to_f64(self) -> f64 {
+                self.into()
+            }
+
+            fn powi(self, n: i32) -> Self {
+                <$ty>::powi(self, n)
+            }
+
+            fn exp(self) -> Self {
+                <$ty>::exp(self)
+            }
+
+            fn round(self) -> Self {
+                <$ty>::round(self)
+            }
+
+            fn is_nan(self) -> bool {
+                <$ty>::is_nan(self)
+            }
+        }
+    )*};
+}
+
+impl_float_value![f32, f64,];
+
+/// Integer types into which values of integer fields can be decoded.
+pub trait IntegerValue: Copy {
+    /// Converts a decoded value into an integer, returning `None` if it is
+    /// out of the range of the type.
+    fn from_f64(value: f64) -> Option<Self>;
+}
+
+impl IntegerValue for i32 {
+    fn from_f64(value: f64) -> Option<Self> {
+        if (f64::from(i32::MIN)..=f64::from(i32::MAX)).contains(&value) {
+            Some(value as i32)
+        } else {
+            None
+        }
+    }
+}
+
+impl IntegerValue for u32 {
+    fn from_f64(value: f64) -> Option<Self> {
+        if (0_f64..=f64::from(u32::MAX)).contains(&value) {
+            Some(value as u32)
+        } else {
+            None
+        }
+    }
+}
+
+/// Decodes values of integer fields (Code Table 5.1 value type 1) as
+/// integers, with `None` for missing values.
+pub fn dispatch_integer<R: Grib2Read, T: IntegerValue>(
+    sect3: &SectionInfo,
+    sect5: &SectionInfo,
+    bitmap: Option<&[u8]>,
+    sect7: &SectionInfo,
+    mut reader: RefMut<R>,
+) -> Result<Box<[Option<T>]>, GribError> {
+    let sect5_body = match &sect5.body {
+        Some(SectionBody::Section5(body)) => body,
+        _ => return Err(GribError::InternalDataError),
+    };
+
+    // Only templates with the type of original field values can have
+    // integer fields.
+    let value_type = match sect5_body.repr_tmpl_num {
+        0 | 1 | 2 | 3 | 40 | 41 | 42 => {
+            let sect5_data = reader.read_sect_body_bytes(sect5)?;
+            sect5_data
+                .get(15)
+                .copied()
+                .and_then(OriginalValueType::from_code)
+        }
+        _ => None,
+    };
+    if value_type != Some(OriginalValueType::Integer) {
+        return Err(GribError::DecodeError(
+            DecodeError::OriginalFieldValueTypeNotInteger,
+        ));
+    }
+
+    let decoded = dispatch::<R, f64>(sect3, sect5, bitmap, sect7, reader)?;
+    to_integers(&decoded).map_err(GribError::DecodeError)
+}
+
+fn to_integers<T: IntegerValue>(values: &[f64]) -> Result<Box<[Option<T>]>, DecodeError> {
+    values
+        .iter()
+        .map(|value| {
+            if value.is_nan() {
+                return Ok(None);
+            }
+            T::from_f64(*value)
+                .map(Some)
+                .ok_or(DecodeError::IntegerValueOutOfRange)
+        })
+        .collect()
+}
+
 trait Grib2DataDecode<R> {
-    fn decode(
+    fn decode<T: FloatValue>(
+        sect3: &SectionInfo,
         sect5: &SectionInfo,
-        sect6: &SectionInfo,
+        bitmap: Option<&[u8]>,
         sect7: &SectionInfo,
         reader: RefMut<R>,
-    ) -> Result<Box<[f32]>, GribError>;
+    ) -> Result<Box<[T]>, GribError>;
 }
 
 struct RunLengthEncodingDecoder {}
 
 impl<R: Grib2Read> Grib2DataDecode<R> for RunLengthEncodingDecoder {
-    fn decode(
+    fn decode<T: FloatValue>(
+        sect3: &SectionInfo,
         sect5: &SectionInfo,
-        sect6: &SectionInfo,
+        bitmap: Option<&[u8]>,
         sect7: &SectionInfo,
         mut reader: RefMut<R>,
-    ) -> Result<Box<[f32]>, GribError> {
-        let (sect5_body, sect6_body) = match (sect5.body.as_ref(), sect6.body.as_ref()) {
-            (Some(SectionBody::Section5(b5)), Some(SectionBody::Section6(b6))) => (b5, b6),
+    ) -> Result<Box<[T]>, GribError> {
+        let (sect3_body, sect5_body) = match (sect3.body.as_ref(), sect5.body.as_ref()) {
+            (Some(SectionBody::Section3(b3)), Some(SectionBody::Section5(b5))) => (b3, b5),
             _ => return Err(GribError::InternalDataError),
         };
 
-        if sect6_body.bitmap_indicator != 255 {
+        let sect5_data = reader.read_sect_body_bytes(sect5)?;
+        if sect5_data.len() < 12 {
             return Err(GribError::DecodeError(
-                DecodeError::BitMapIndicatorUnsupported,
+                DecodeError::RunLengthEncodingDecodeError(
+                    RunLengthEncodingDecodeError::LengthMismatch,
+                ),
             ));
         }
-
-        let sect5_data = reader.read_sect_body_bytes(sect5)?;
         let nbit = read_as!(u8, sect5_data, 6);
         let maxv = read_as!(u16, sect5_data, 7);
         let max_level = read_as!(u16, sect5_data, 9);
         let num_digits = read_as!(u8, sect5_data, 11);
 
+        // Levels used in the data should be defined in the level map.
+        if maxv > max_level {
+            return Err(GribError::DecodeError(
+                DecodeError::RunLengthEncodingDecodeError(
+                    RunLengthEncodingDecodeError::InvalidLevelValue(maxv),
+                ),
+            ));
+        }
+        if sect5_data.len() < 12 + usize::from(max_level) * 2 {
+            return Err(GribError::DecodeError(
+                DecodeError::RunLengthEncodingDecodeError(
+                    RunLengthEncodingDecodeError::LengthMismatch,
+                ),
+            ));
+        }
+
         let mut level_map = Vec::with_capacity(max_level.into());
-        level_map.push(f32::NAN);
+        level_map.push(T::NAN);
         let mut pos = 12;
 
         for _ in 0..max_level {
-            let val: f32 = read_as!(u16, sect5_data, pos).into();
+            let val = T::from(read_as!(u16, sect5_data, pos).into());
             let num_digits: i32 = num_digits.into();
-            let factor = 10_f32.powi(-num_digits);
+            let factor = T::from(10.).powi(-num_digits);
             let val = val * factor;
             level_map.push(val);
             pos += std::mem::size_of::<u16>();
@@ -126,13 +527,13 @@ impl<R: Grib2Read> Grib2DataDecode<R> for RunLengthEncodingDecoder {
             maxv,
             Some(sect5_body.num_points as usize),
         )
-        .map_err(|e| DecodeError::RunLengthEncodingDecodeError(e))?;
+        .map_err(DecodeError::RunLengthEncodingDecodeError)?;
 
-        let level_to_value = |level: &u8| -> Result<f32, DecodeError> {
+        let level_to_value = |level: &u16| -> Result<T, DecodeError> {
             let index: usize = (*level).into();
             level_map
                 .get(index)
-                .map(|l| *l)
+                .copied()
                 .ok_or(DecodeError::RunLengthEncodingDecodeError(
                     RunLengthEncodingDecodeError::InvalidLevelValue(*level),
                 ))
@@ -140,42 +541,73 @@ impl<R: Grib2Read> Grib2DataDecode<R> for RunLengthEncodingDecoder {
 
         let decoded: Result<Vec<_>, _> = (*decoded_levels).iter().map(level_to_value).collect();
         let decoded = decoded?.into_boxed_slice();
+
+        let decoded = match bitmap {
+            Some(bitmap) => apply_bitmap(&decoded, bitmap, sect3_body.num_points as usize)?,
+            None => decoded,
+        };
         Ok(decoded)
     }
 }
 
+/// Decodes levels from `nbit`-bit codes, where codes up to `maxv` are
+/// levels and larger codes are digits of the run length of the preceding
+/// level, least significant first.
 fn rleunpack(
     input: &[u8],
     nbit: u8,
     maxv: u16,
     expected_len: Option<usize>,
-) -> Result<Box<[u8]>, RunLengthEncodingDecodeError> {
-    if nbit != 8 {
+) -> Result<Box<[u16]>, RunLengthEncodingDecodeError> {
+    if nbit == 0 || nbit > 16 {
         return Err(RunLengthEncodingDecodeError::NotSupported);
     }
+    let nbit = usize::from(nbit);
+    let rlbase = u32::from(maxv) + 1;
+    if rlbase > 1 << nbit {
+        return Err(RunLengthEncodingDecodeError::InvalidLevelValue(maxv));
+    }
 
     let mut out_buf = match expected_len {
         Some(sz) => Vec::with_capacity(sz),
         None => Vec::new(),
     };
 
-    let rlbase = maxv + 1;
-    let lngu: usize = (2u16.pow(nbit.into()) - rlbase).into();
+    let lngu = ((1 << nbit) - rlbase) as usize;
     let mut cached = None;
-    let mut exp: usize = 1;
-
-    for value in input.iter() {
-        let value = *value;
+    // The place value of the next digit, which is `None` once it exceeds
+    // `usize`
+    let mut exp = Some(1_usize);
+    let mut reader = BitReader::new(input);
 
-        if rlbase > value.into() {
+    while let Some(value) = reader.read(nbit) {
+        if rlbase > value {
+            // Codes in the padding at the end of the data are ignored.
+            if expected_len == Some(out_buf.len()) {
+                break;
+            }
+            let value = value as u16;
             out_buf.push(value);
             cached = Some(value);
-            exp = 1;
+            exp = Some(1);
         } else {
             let prev = cached.ok_or(RunLengthEncodingDecodeError::InvalidFirstValue)?;
-            let length: usize = ((value as u16 - rlbase) as usize) * exp;
-            out_buf.append(&mut vec![prev; length]);
-            exp *= lngu;
+            let digit = (value - rlbase) as usize;
+            let length = match digit {
+                0 => 0,
+                _ => exp
+                    .and_then(|exp| digit.checked_mul(exp))
+                    .ok_or(RunLengthEncodingDecodeError::LengthMismatch)?,
+            };
+            // Runs are checked before being expanded, so that malformed
+            // data cannot request huge allocations.
+            let new_len = out_buf
+                .len()
+                .checked_add(length)
+                .filter(|new_len| expected_len.is_none_or(|len| *new_len <= len))
+                .ok_or(RunLengthEncodingDecodeError::LengthMismatch)?;
+            out_buf.resize(new_len, prev);
+            exp = exp.and_then(|exp| exp.checked_mul(lngu));
         }
     }
 
@@ -191,147 +623,1791 @@ fn rleunpack(
 struct SimplePackingDecoder {}
 
 impl<R: Grib2Read> Grib2DataDecode<R> for SimplePackingDecoder {
-    fn decode(
+    fn decode<T: FloatValue>(
+        sect3: &SectionInfo,
         sect5: &SectionInfo,
-        sect6: &SectionInfo,
+        bitmap: Option<&[u8]>,
         sect7: &SectionInfo,
         mut reader: RefMut<R>,
-    ) -> Result<Box<[f32]>, GribError> {
-        let (sect5_body, sect6_body) = match (sect5.body.as_ref(), sect6.body.as_ref()) {
-            (Some(SectionBody::Section5(b5)), Some(SectionBody::Section6(b6))) => (b5, b6),
+    ) -> Result<Box<[T]>, GribError> {
+        let (sect3_body, sect5_body) = match (sect3.body.as_ref(), sect5.body.as_ref()) {
+            (Some(SectionBody::Section3(b3)), Some(SectionBody::Section5(b5))) => (b3, b5),
             _ => return Err(GribError::InternalDataError),
         };
 
-        if sect6_body.bitmap_indicator != 255 {
-            return Err(GribError::DecodeError(
-                DecodeError::BitMapIndicatorUnsupported,
-            ));
-        }
-
         let sect5_data = reader.read_sect_body_bytes(sect5)?;
-        let ref_val = read_as!(f32, sect5_data, 6);
-        let exp = read_as!(u16, sect5_data, 10).into_grib_int();
-        let dig = read_as!(u16, sect5_data, 12).into_grib_int();
-        let nbit = read_as!(u8, sect5_data, 14);
-        let value_type = read_as!(u8, sect5_data, 15);
-
-        if value_type != 0 {
-            return Err(GribError::DecodeError(
-                DecodeError::SimplePackingDecodeError(
-                    SimplePackingDecodeError::OriginalFieldValueTypeNotSupported,
-                ),
-            ));
-        }
+        let param = SimplePackingParam::from_bytes(sect5_body.repr_tmpl_num, &sect5_data)
+            .map_err(DecodeError::SimplePackingDecodeError)?;
+        let num_values = match param.variant {
+            SimplePackingVariant::Matrix { num_values, .. } => num_values,
+            _ => sect5_body.num_points as usize,
+        };
 
         let sect7_data = reader.read_sect_body_bytes(sect7)?;
 
-        let decoded = unpack_simple_packing(
+        let mut decoded = unpack_simple_packing(
             &sect7_data,
-            nbit,
-            ref_val,
-            exp,
-            dig,
-            Some(sect5_body.num_points as usize),
+            param.nbit,
+            param.ref_val,
+            param.exp,
+            param.dig,
+            Some(num_values),
         )
-        .map_err(|e| DecodeError::SimplePackingDecodeError(e))?;
+        .map_err(DecodeError::SimplePackingDecodeError)?;
+
+        match param.variant {
+            SimplePackingVariant::Logarithmic(preprocessing) => {
+                reverse_logarithmic_preprocessing(&mut decoded, preprocessing)
+            }
+            _ => param.value_type.restore(&mut decoded),
+        }
+
+        let num_points = sect3_body.num_points as usize;
+        let decoded = match (bitmap, param.variant) {
+            (
+                Some(bitmap),
+                SimplePackingVariant::Matrix {
+                    num_rows, num_cols, ..
+                },
+            ) => {
+                let matrix_size = num_rows * num_cols;
+                let bitmap = repeat_bits(bitmap, num_points, matrix_size);
+                apply_bitmap(&decoded, &bitmap, num_points * matrix_size)?
+            }
+            (Some(bitmap), _) => apply_bitmap(&decoded, bitmap, num_points)?,
+            (None, _) => decoded,
+        };
         Ok(decoded)
     }
 }
 
-fn unpack_simple_packing(
-    input: &[u8],
-    nbit: u8,
+/// Parameters of simple packing (Template 5.0) and the templates which
+/// extend it.
+#[derive(Debug, PartialEq)]
+struct SimplePackingParam {
     ref_val: f32,
     exp: i16,
     dig: i16,
-    expected_len: Option<usize>,
-) -> Result<Box<[f32]>, SimplePackingDecodeError> {
-    if nbit != 16 {
-        return Err(SimplePackingDecodeError::NotSupported);
-    }
+    nbit: u8,
+    value_type: OriginalValueType,
+    variant: SimplePackingVariant,
+}
 
-    let mut out_buf = match expected_len {
-        Some(sz) => Vec::with_capacity(sz),
-        None => Vec::new(),
-    };
+#[derive(Debug, PartialEq)]
+enum SimplePackingVariant {
+    Plain,
+    /// Matrix values at grid points (Template 5.1), which are packed matrix
+    /// by matrix with `num_rows` x `num_cols` values for each grid point
+    Matrix {
+        num_values: usize,
+        num_rows: usize,
+        num_cols: usize,
+    },
+    /// Logarithmic pre-processing (Template 5.61) with the pre-processing
+    /// parameter B
+    Logarithmic(f32),
+}
 
-    let dig: i32 = dig.into();
-    let mut pos = 0;
+impl SimplePackingParam {
+    fn from_bytes(tmpl_num: u16, buf: &[u8]) -> Result<Self, SimplePackingDecodeError> {
+        let min_len = match tmpl_num {
+            1 => 25,
+            61 => 19,
+            _ => 16,
+        };
+        if buf.len() < min_len {
+            return Err(SimplePackingDecodeError::LengthMismatch);
+        }
+
+        // Template 5.61 has no type of original field values.
+        let value_type = match tmpl_num {
+            61 => OriginalValueType::FloatingPoint,
+            _ => OriginalValueType::from_code(read_as!(u8, buf, 15))
+                .ok_or(SimplePackingDecodeError::OriginalFieldValueTypeNotSupported)?,
+        };
 
-    while pos < input.len() {
-        let encoded = read_as!(u16, input, pos) as f32;
-        pos += std::mem::size_of::<u16>();
+        let variant = match tmpl_num {
+            1 => {
+                // The matrix bit-map indicator (see Code Table 6.0) is 255
+                // when no matrix bit maps are present.
+                if read_as!(u8, buf, 16) != 255 {
+                    return Err(SimplePackingDecodeError::MatrixBitmapsNotSupported);
+                }
+                SimplePackingVariant::Matrix {
+                    num_values: read_as!(u32, buf, 17) as usize,
+                    num_rows: usize::from(read_as!(u16, buf, 21)),
+                    num_cols: usize::from(read_as!(u16, buf, 23)),
+                }
+            }
+            61 => SimplePackingVariant::Logarithmic(read_as!(f32, buf, 15)),
+            _ => SimplePackingVariant::Plain,
+        };
 
-        let diff = (encoded * 2_f32.powi(exp.into())) as f32;
-        let dig_factor = 10_f32.powi(-dig);
-        let value: f32 = (ref_val + diff) * dig_factor;
-        out_buf.push(value);
+        Ok(Self {
+            ref_val: read_as!(f32, buf, 6),
+            exp: read_as!(u16, buf, 10).into_grib_int(),
+            dig: read_as!(u16, buf, 12).into_grib_int(),
+            nbit: read_as!(u8, buf, 14),
+            value_type,
+            variant,
+        })
     }
+}
 
-    if let Some(len) = expected_len {
-        if len != out_buf.len() {
-            return Err(SimplePackingDecodeError::LengthMismatch);
+/// Restores values Y transformed into Z = ln(Y + B) before packing.
+fn reverse_logarithmic_preprocessing<T: FloatValue>(values: &mut [T], preprocessing: f32) {
+    for value in values.iter_mut() {
+        *value = value.exp() - T::from(preprocessing);
+    }
+}
+
+/// Repeats each of the first `num_bits` bits of `bitmap` `times` times, so
+/// that a bit map of grid points applies to all values of the points.
+fn repeat_bits(bitmap: &[u8], num_bits: usize, times: usize) -> Box<[u8]> {
+    let mut out_buf = vec![0_u8; (num_bits * times).div_ceil(8)];
+    let mut reader = BitReader::new(bitmap);
+    for i in 0..num_bits {
+        if reader.read(1) == Some(1) {
+            for pos in (i * times)..((i + 1) * times) {
+                out_buf[pos / 8] |= 0x80 >> (pos % 8);
+            }
         }
     }
+    out_buf.into_boxed_slice()
+}
 
+/// Expands `values` to `num_points` grid points according to `bitmap`,
+/// filling points without values with NaN.
+fn apply_bitmap<T: Copy + From<f32>>(
+    values: &[T],
+    bitmap: &[u8],
+    num_points: usize,
+) -> Result<Box<[T]>, DecodeError> {
+    let mut out_buf = vec![T::from(f32::NAN); num_points];
+    apply_bitmap_into(values, bitmap, &mut out_buf)?;
     Ok(out_buf.into_boxed_slice())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Expands `values` to the grid points of `out` according to `bitmap`,
+/// filling points without values with NaN.
+fn apply_bitmap_into<T: Copy + From<f32>>(
+    values: &[T],
+    bitmap: &[u8],
+    out: &mut [T],
+) -> Result<(), DecodeError> {
+    if bitmap.len() * 8 < out.len() {
+        return Err(DecodeError::BitMapLengthMismatch);
+    }
 
-    // This test data is based on 4-bit numbers of sample data
-    // provided in JMBSC's document available in the following URL:
-    // http://www.jmbsc.or.jp/jp/online/joho-sample/Run-Length_Encoding.pdf
-    #[test]
-    fn rleunpack_u8() {
-        let input: Vec<u8> = vec![3, 9, 12, 6, 4, 15, 2, 1, 0, 13, 12, 2, 3];
-        let output: Vec<u8> = vec![
-            3, 9, 9, 6, 4, 4, 4, 4, 4, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 2, 3,
-        ];
-        let input: Vec<u8> = input.iter().map(|n| n + 240).collect();
-        let output: Vec<u8> = output.iter().map(|n| n + 240).collect();
+    let mut values = values.iter();
+    let mut reader = BitReader::new(bitmap);
+    for out_value in out.iter_mut() {
+        *out_value = match reader.read(1) {
+            Some(1) => *values.next().ok_or(DecodeError::BitMapLengthMismatch)?,
+            _ => T::from(f32::NAN),
+        };
+    }
 
-        assert_eq!(
-            rleunpack(&input, 8, 250, Some(21)),
-            Ok(output.into_boxed_slice())
-        );
+    if values.next().is_some() {
+        return Err(DecodeError::BitMapLengthMismatch);
     }
 
-    #[test]
-    fn rleunpack_u8_long_length() {
-        let input: Vec<u8> = vec![0x00, 0x14, 0x1c];
-        let output: Vec<u8> = vec![0; 6065];
+    Ok(())
+}
 
-        assert_eq!(rleunpack(&input, 8, 3, None), Ok(output.into_boxed_slice()));
+/// Type of original field values (see Code Table 5.1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OriginalValueType {
+    FloatingPoint,
+    Integer,
+}
+
+impl OriginalValueType {
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Self::FloatingPoint),
+            1 => Some(Self::Integer),
+            _ => None,
+        }
     }
 
-    #[test]
-    fn simple_packing_u8() {
-        let ref_val_bytes = vec![0x35, 0x3e, 0x6b, 0xf6];
-        let exp = 0x801a;
-        let dig = 0x0000;
-        let input: Vec<u8> = vec![0x00, 0x06, 0x00, 0x0d];
-        let expected: Vec<f32> = vec![7.98783162e-07, 9.03091291e-07];
+    /// Rounds values of integer fields to the nearest integer, removing
+    /// errors introduced by the scaling of packed values.
+    fn restore<T: FloatValue>(self, values: &mut [T]) {
+        if self == Self::Integer {
+            for value in values.iter_mut() {
+                *value = value.round();
+            }
+        }
+    }
+}
 
-        let ref_val = f32::from_be_bytes(ref_val_bytes[..].try_into().unwrap());
-        let actual = unpack_simple_packing(
-            &input,
-            16,
-            ref_val,
-            exp.into_grib_int(),
-            dig.into_grib_int(),
-            Some(2),
-        )
-        .unwrap();
+/// Returns the value of all grid points if the field is constant.
+///
+/// A constant field has no bits per value, and all its values are equal to
+/// the reference value.  Section 7 of such a field may contain no data.
+fn constant_field_if_nbit_zero<T: FloatValue>(nbit: u8, scaling: &Scaling<T>) -> Option<T> {
+    (nbit == 0).then(|| scaling.apply(0))
+}
 
-        assert_eq!(actual.len(), expected.len());
-        let mut i = 0;
-        while i < actual.len() {
-            assert!(actual[i] < expected[i] + 0.00000001);
-            assert!(actual[i] > expected[i] - 0.00000001);
-            i += 1;
+/// Parameters to restore original values from packed integers, shared by
+/// simple packing and the templates derived from it.
+struct Scaling<T> {
+    ref_val: T,
+    exp_factor: T,
+    dig_factor: T,
+}
+
+impl<T: FloatValue> Scaling<T> {
+    fn new(ref_val: f32, exp: i16, dig: i16) -> Self {
+        Self {
+            ref_val: T::from(ref_val),
+            exp_factor: T::from(2.).powi(exp.into()),
+            dig_factor: T::from(10.).powi(-i32::from(dig)),
         }
     }
+
+    /// Computes Y = (R + X * 2^E) / 10^D for a packed integer X.
+    fn apply(&self, encoded: i64) -> T {
+        (self.ref_val + T::from_i64(encoded) * self.exp_factor) * self.dig_factor
+    }
+}
+
+fn unpack_simple_packing<T: FloatValue>(
+    input: &[u8],
+    nbit: u8,
+    ref_val: f32,
+    exp: i16,
+    dig: i16,
+    expected_len: Option<usize>,
+) -> Result<Box<[T]>, SimplePackingDecodeError> {
+    let mut out_buf = Vec::new();
+    unpack_simple_packing_into(input, nbit, ref_val, exp, dig, expected_len, &mut out_buf)?;
+    Ok(out_buf.into_boxed_slice())
+}
+
+/// Unpacks values into `out`, replacing its contents.
+fn unpack_simple_packing_into<T: FloatValue>(
+    input: &[u8],
+    nbit: u8,
+    ref_val: f32,
+    exp: i16,
+    dig: i16,
+    expected_len: Option<usize>,
+    out: &mut Vec<T>,
+) -> Result<(), SimplePackingDecodeError> {
+    if nbit > 32 {
+        return Err(SimplePackingDecodeError::NotSupported);
+    }
+
+    let scaling = Scaling::new(ref_val, exp, dig);
+    out.clear();
+    if let Some(value) = constant_field_if_nbit_zero(nbit, &scaling) {
+        out.resize(expected_len.unwrap_or(0), value);
+        return Ok(());
+    }
+
+    let nbit = usize::from(nbit);
+    let len = expected_len.unwrap_or(input.len() * 8 / nbit);
+    out.reserve(len);
+    let mut reader = BitReader::new(input);
+
+    for _ in 0..len {
+        let encoded = reader
+            .read(nbit)
+            .ok_or(SimplePackingDecodeError::LengthMismatch)?;
+        out.push(scaling.apply(encoded.into()));
+    }
+
+    Ok(())
+}
+
+struct IeeeFloatDecoder {}
+
+impl<R: Grib2Read> Grib2DataDecode<R> for IeeeFloatDecoder {
+    fn decode<T: FloatValue>(
+        sect3: &SectionInfo,
+        sect5: &SectionInfo,
+        bitmap: Option<&[u8]>,
+        sect7: &SectionInfo,
+        mut reader: RefMut<R>,
+    ) -> Result<Box<[T]>, GribError> {
+        let (sect3_body, sect5_body) = match (sect3.body.as_ref(), sect5.body.as_ref()) {
+            (Some(SectionBody::Section3(b3)), Some(SectionBody::Section5(b5))) => (b3, b5),
+            _ => return Err(GribError::InternalDataError),
+        };
+
+        let sect5_data = reader.read_sect_body_bytes(sect5)?;
+        let precision =
+            ieee_float_precision(&sect5_data).map_err(DecodeError::IeeeFloatDecodeError)?;
+
+        let sect7_data = reader.read_sect_body_bytes(sect7)?;
+
+        let decoded = unpack_ieee_float(&sect7_data, precision, sect5_body.num_points as usize)
+            .map_err(DecodeError::IeeeFloatDecodeError)?;
+
+        let decoded = match bitmap {
+            Some(bitmap) => apply_bitmap(&decoded, bitmap, sect3_body.num_points as usize)?,
+            None => decoded,
+        };
+        Ok(decoded)
+    }
+}
+
+/// Reads the precision of floating-point numbers (see Code Table 5.7) from
+/// Section 5 of Template 5.4.
+fn ieee_float_precision(sect5_data: &[u8]) -> Result<u8, IeeeFloatDecodeError> {
+    if sect5_data.len() < 7 {
+        return Err(IeeeFloatDecodeError::LengthMismatch);
+    }
+    Ok(read_as!(u8, sect5_data, 6))
+}
+
+fn unpack_ieee_float<T: FloatValue>(
+    input: &[u8],
+    precision: u8,
+    expected_len: usize,
+) -> Result<Box<[T]>, IeeeFloatDecodeError> {
+    let mut out_buf = Vec::new();
+    unpack_ieee_float_into(input, precision, expected_len, &mut out_buf)?;
+    Ok(out_buf.into_boxed_slice())
+}
+
+/// Unpacks values into `out`, replacing its contents.
+fn unpack_ieee_float_into<T: FloatValue>(
+    input: &[u8],
+    precision: u8,
+    expected_len: usize,
+    out: &mut Vec<T>,
+) -> Result<(), IeeeFloatDecodeError> {
+    let size = match precision {
+        1 => 4,
+        2 => 8,
+        _ => return Err(IeeeFloatDecodeError::PrecisionNotSupported(precision)),
+    };
+    if input.len() < expected_len * size {
+        return Err(IeeeFloatDecodeError::LengthMismatch);
+    }
+
+    out.clear();
+    out.extend(
+        input
+            .chunks_exact(size)
+            .take(expected_len)
+            .map(|bytes| match precision {
+                1 => T::from(f32::from_be_bytes(bytes.try_into().unwrap())),
+                _ => T::from_f64(f64::from_be_bytes(bytes.try_into().unwrap())),
+            }),
+    );
+    Ok(())
+}
+
+struct ComplexPackingDecoder {}
+
+impl<R: Grib2Read> Grib2DataDecode<R> for ComplexPackingDecoder {
+    fn decode<T: FloatValue>(
+        sect3: &SectionInfo,
+        sect5: &SectionInfo,
+        bitmap: Option<&[u8]>,
+        sect7: &SectionInfo,
+        mut reader: RefMut<R>,
+    ) -> Result<Box<[T]>, GribError> {
+        let (sect3_body, sect5_body) = match (sect3.body.as_ref(), sect5.body.as_ref()) {
+            (Some(SectionBody::Section3(b3)), Some(SectionBody::Section5(b5))) => (b3, b5),
+            _ => return Err(GribError::InternalDataError),
+        };
+
+        let sect5_data = reader.read_sect_body_bytes(sect5)?;
+        let param = ComplexPackingParam::from_bytes(sect5_body.repr_tmpl_num, &sect5_data)
+            .map_err(DecodeError::ComplexPackingDecodeError)?;
+
+        let sect7_data = reader.read_sect_body_bytes(sect7)?;
+
+        let decoded = unpack_complex_packing(&sect7_data, &param, sect5_body.num_points as usize)
+            .map_err(DecodeError::ComplexPackingDecodeError)?;
+
+        let decoded = match bitmap {
+            Some(bitmap) => apply_bitmap(&decoded, bitmap, sect3_body.num_points as usize)?,
+            None => decoded,
+        };
+        Ok(decoded)
+    }
+}
+
+struct ComplexPackingParam {
+    ref_val: f32,
+    exp: i16,
+    dig: i16,
+    group_ref_nbit: u8,
+    value_type: OriginalValueType,
+    /// Group splitting method used (see Code Table 5.4)
+    group_splitting: u8,
+    /// Missing value management used (see Code Table 5.5)
+    missing_management: u8,
+    num_groups: usize,
+    group_width_ref: u8,
+    group_width_nbit: u8,
+    group_len_ref: u32,
+    group_len_inc: u8,
+    /// True length of the last group
+    group_len_last: u32,
+    group_len_nbit: u8,
+    /// Parameters of spatial differencing used in Template 5.3
+    spatial_differencing: Option<SpatialDifferencingParam>,
+}
+
+struct SpatialDifferencingParam {
+    /// Order of spatial differencing (see Code Table 5.6)
+    order: u8,
+    /// Number of octets required in the data section to specify extra
+    /// descriptors needed for spatial differencing
+    num_octets: u8,
+}
+
+impl ComplexPackingParam {
+    fn from_bytes(tmpl_num: u16, buf: &[u8]) -> Result<Self, ComplexPackingDecodeError> {
+        let with_spatial_differencing = tmpl_num == 3;
+        let min_len = if with_spatial_differencing { 44 } else { 42 };
+        if buf.len() < min_len {
+            return Err(ComplexPackingDecodeError::LengthMismatch);
+        }
+        let value_type = OriginalValueType::from_code(read_as!(u8, buf, 15))
+            .ok_or(ComplexPackingDecodeError::OriginalFieldValueTypeNotSupported)?;
+
+        Ok(Self {
+            ref_val: read_as!(f32, buf, 6),
+            exp: read_as!(u16, buf, 10).into_grib_int(),
+            dig: read_as!(u16, buf, 12).into_grib_int(),
+            group_ref_nbit: read_as!(u8, buf, 14),
+            value_type,
+            group_splitting: read_as!(u8, buf, 16),
+            missing_management: read_as!(u8, buf, 17),
+            num_groups: read_as!(u32, buf, 26) as usize,
+            group_width_ref: read_as!(u8, buf, 30),
+            group_width_nbit: read_as!(u8, buf, 31),
+            group_len_ref: read_as!(u32, buf, 32),
+            group_len_inc: read_as!(u8, buf, 36),
+            group_len_last: read_as!(u32, buf, 37),
+            group_len_nbit: read_as!(u8, buf, 41),
+            spatial_differencing: if with_spatial_differencing {
+                Some(SpatialDifferencingParam {
+                    order: read_as!(u8, buf, 42),
+                    num_octets: read_as!(u8, buf, 43),
+                })
+            } else {
+                None
+            },
+        })
+    }
+}
+
+/// Integer value unpacked from groups, before being scaled into the
+/// original value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GroupedValue {
+    Value(i64),
+    PrimaryMissing,
+    SecondaryMissing,
+}
+
+fn unpack_complex_packing<T: FloatValue>(
+    input: &[u8],
+    param: &ComplexPackingParam,
+    expected_len: usize,
+) -> Result<Box<[T]>, ComplexPackingDecodeError> {
+    let mut out_buf = Vec::new();
+    unpack_complex_packing_into(input, param, expected_len, &mut Vec::new(), &mut out_buf)?;
+    Ok(out_buf.into_boxed_slice())
+}
+
+/// Unpacks values into `out`, replacing its contents, with `groups` holding
+/// the values unpacked from groups.
+fn unpack_complex_packing_into<T: FloatValue>(
+    input: &[u8],
+    param: &ComplexPackingParam,
+    expected_len: usize,
+    groups: &mut Vec<GroupedValue>,
+    out: &mut Vec<T>,
+) -> Result<(), ComplexPackingDecodeError> {
+    match &param.spatial_differencing {
+        Some(differencing) => {
+            let mut reader = BitReader::new(input);
+            let (first_values, minimum) = read_extra_descriptors(&mut reader, differencing)?;
+            // Groups start after the `order + 1` extra descriptors.
+            let start =
+                (usize::from(differencing.order) + 1) * usize::from(differencing.num_octets);
+            unpack_groups(&input[start..], param, expected_len, groups)?;
+            let first_values = &first_values[..usize::from(differencing.order)];
+            reverse_spatial_differencing(groups, first_values, minimum);
+        }
+        None => unpack_groups(input, param, expected_len, groups)?,
+    }
+
+    let scaling = Scaling::new(param.ref_val, param.exp, param.dig);
+    out.clear();
+    out.extend(groups.iter().map(|value| match value {
+        GroupedValue::Value(encoded) => scaling.apply(*encoded),
+        _ => T::NAN,
+    }));
+    param.value_type.restore(out);
+    Ok(())
+}
+
+/// Reads the first values of the original (undifferenced) data and the
+/// overall minimum of the differences stored at the beginning of
+/// Section 7 of Template 5.3.  Only the first `order` elements of the
+/// returned array are first values.
+fn read_extra_descriptors(
+    reader: &mut BitReader,
+    param: &SpatialDifferencingParam,
+) -> Result<([i64; 2], i64), ComplexPackingDecodeError> {
+    if !(1..=2).contains(&param.order) {
+        return Err(ComplexPackingDecodeError::SpatialDifferencingOrderNotSupported(param.order));
+    }
+    if !(1..=4).contains(&param.num_octets) {
+        return Err(ComplexPackingDecodeError::LengthMismatch);
+    }
+
+    // All extra descriptors are sign-magnitude integers, as read by NCEP
+    // g2clib (comunpack.c) and wgrib2 (unpk_complex.c).
+    let nbit = usize::from(param.num_octets) * 8;
+    let mut read_signed = || match (reader.read(1), reader.read(nbit - 1)) {
+        (Some(0), Some(magnitude)) => Ok(i64::from(magnitude)),
+        (Some(_), Some(magnitude)) => Ok(-i64::from(magnitude)),
+        _ => Err(ComplexPackingDecodeError::LengthMismatch),
+    };
+    let mut first_values = [0_i64; 2];
+    for value in first_values.iter_mut().take(param.order.into()) {
+        *value = read_signed()?;
+    }
+    let minimum = read_signed()?;
+    Ok((first_values, minimum))
+}
+
+/// Restores the original values from differences of first or second
+/// order, which are taken only over non-missing values.
+fn reverse_spatial_differencing(values: &mut [GroupedValue], first_values: &[i64], minimum: i64) {
+    let order = first_values.len();
+    let (mut prev1, mut prev2) = (0_i64, 0_i64);
+    let mut n = 0;
+
+    for value in values.iter_mut() {
+        let value = match value {
+            GroupedValue::Value(value) => value,
+            _ => continue,
+        };
+        *value = if n < order {
+            first_values[n]
+        } else if order == 1 {
+            value.wrapping_add(minimum).wrapping_add(prev1)
+        } else {
+            value
+                .wrapping_add(minimum)
+                .wrapping_add(prev1.wrapping_mul(2))
+                .wrapping_sub(prev2)
+        };
+        prev2 = prev1;
+        prev1 = *value;
+        n += 1;
+    }
+}
+
+/// Reads group reference values, group widths, group lengths and
+/// values packed in groups from `input` into `out`, replacing its
+/// contents.
+fn unpack_groups(
+    input: &[u8],
+    param: &ComplexPackingParam,
+    expected_len: usize,
+    out: &mut Vec<GroupedValue>,
+) -> Result<(), ComplexPackingDecodeError> {
+    if param.group_splitting != 1 {
+        return Err(ComplexPackingDecodeError::GroupSplittingMethodNotSupported(
+            param.group_splitting,
+        ));
+    }
+    if param.missing_management > 2 {
+        return Err(
+            ComplexPackingDecodeError::MissingValueManagementNotSupported(param.missing_management),
+        );
+    }
+
+    let nbits = [
+        param.group_ref_nbit,
+        param.group_width_nbit,
+        param.group_len_nbit,
+    ];
+    if nbits.iter().any(|nbit| *nbit > 32) {
+        return Err(ComplexPackingDecodeError::LengthMismatch);
+    }
+
+    // Group reference values, group widths and group lengths are each
+    // padded to a whole octet and followed by the packed values.
+    let num_octets = |nbit: u8| (param.num_groups * usize::from(nbit)).div_ceil(8);
+    let widths_start = num_octets(param.group_ref_nbit);
+    let lengths_start = widths_start + num_octets(param.group_width_nbit);
+    let values_start = lengths_start + num_octets(param.group_len_nbit);
+    if input.len() < values_start {
+        return Err(ComplexPackingDecodeError::LengthMismatch);
+    }
+    let refs = &input[..widths_start];
+    let widths = &input[widths_start..lengths_start];
+    let lengths = &input[lengths_start..values_start];
+
+    let group_len = |i: usize, scaled: u32| -> u64 {
+        if i == param.num_groups - 1 {
+            u64::from(param.group_len_last)
+        } else {
+            u64::from(param.group_len_ref) + u64::from(param.group_len_inc) * u64::from(scaled)
+        }
+    };
+    let mut reader = BitReader::new(lengths);
+    let mut total_len = 0_u64;
+    for i in 0..param.num_groups {
+        let scaled = reader
+            .read(param.group_len_nbit.into())
+            .ok_or(ComplexPackingDecodeError::LengthMismatch)?;
+        total_len += group_len(i, scaled);
+    }
+    if total_len != expected_len as u64 {
+        return Err(ComplexPackingDecodeError::LengthMismatch);
+    }
+
+    // Missing values are represented with the largest values (and the
+    // second largest values for secondary missing values) of the bit
+    // widths used.
+    let missing = |value: u32, nbit: u32| -> Option<GroupedValue> {
+        let max = if nbit == 32 {
+            u32::MAX
+        } else {
+            (1_u32 << nbit).wrapping_sub(1)
+        };
+        match param.missing_management {
+            1 | 2 if value == max => Some(GroupedValue::PrimaryMissing),
+            2 if value == max.wrapping_sub(1) => Some(GroupedValue::SecondaryMissing),
+            _ => None,
+        }
+    };
+
+    let mut refs = BitReader::new(refs);
+    let mut widths = BitReader::new(widths);
+    let mut lengths = BitReader::new(lengths);
+    let mut reader = BitReader::new(&input[values_start..]);
+    out.clear();
+    out.reserve(expected_len);
+    for i in 0..param.num_groups {
+        let (group_ref, width, scaled) = match (
+            refs.read(param.group_ref_nbit.into()),
+            widths.read(param.group_width_nbit.into()),
+            lengths.read(param.group_len_nbit.into()),
+        ) {
+            (Some(group_ref), Some(width), Some(scaled)) => (group_ref, width, scaled),
+            _ => return Err(ComplexPackingDecodeError::LengthMismatch),
+        };
+        let width = u32::from(param.group_width_ref) + width;
+        if width > 32 {
+            return Err(ComplexPackingDecodeError::LengthMismatch);
+        }
+        let len = group_len(i, scaled) as usize;
+
+        if width == 0 {
+            let value = missing(group_ref, param.group_ref_nbit.into())
+                .unwrap_or(GroupedValue::Value(group_ref.into()));
+            out.extend(std::iter::repeat_n(value, len));
+            continue;
+        }
+
+        for _ in 0..len {
+            let packed = reader
+                .read(width as usize)
+                .ok_or(ComplexPackingDecodeError::LengthMismatch)?;
+            let value = missing(packed, width).unwrap_or(GroupedValue::Value(
+                i64::from(group_ref) + i64::from(packed),
+            ));
+            out.push(value);
+        }
+    }
+
+    Ok(())
+}
+
+struct Jpeg2000CodeStreamDecoder {}
+
+impl<R: Grib2Read> Grib2DataDecode<R> for Jpeg2000CodeStreamDecoder {
+    fn decode<T: FloatValue>(
+        sect3: &SectionInfo,
+        sect5: &SectionInfo,
+        bitmap: Option<&[u8]>,
+        sect7: &SectionInfo,
+        mut reader: RefMut<R>,
+    ) -> Result<Box<[T]>, GribError> {
+        let (sect3_body, sect5_body) = match (sect3.body.as_ref(), sect5.body.as_ref()) {
+            (Some(SectionBody::Section3(b3)), Some(SectionBody::Section5(b5))) => (b3, b5),
+            _ => return Err(GribError::InternalDataError),
+        };
+
+        let sect5_data = reader.read_sect_body_bytes(sect5)?;
+        if sect5_data.len() < 18 {
+            return Err(GribError::DecodeError(
+                DecodeError::Jpeg2000CodeStreamDecodeError(
+                    Jpeg2000CodeStreamDecodeError::LengthMismatch,
+                ),
+            ));
+        }
+        let ref_val = read_as!(f32, sect5_data, 6);
+        let exp = read_as!(u16, sect5_data, 10).into_grib_int();
+        let dig = read_as!(u16, sect5_data, 12).into_grib_int();
+        let nbit = read_as!(u8, sect5_data, 14);
+        let value_type = OriginalValueType::from_code(read_as!(u8, sect5_data, 15)).ok_or(
+            DecodeError::Jpeg2000CodeStreamDecodeError(
+                Jpeg2000CodeStreamDecodeError::OriginalFieldValueTypeNotSupported,
+            ),
+        )?;
+
+        let sect7_data = reader.read_sect_body_bytes(sect7)?;
+
+        let mut decoded = unpack_jpeg2000_code_stream(
+            &sect7_data,
+            nbit,
+            &Scaling::new(ref_val, exp, dig),
+            sect5_body.num_points as usize,
+        )
+        .map_err(DecodeError::Jpeg2000CodeStreamDecodeError)?;
+        value_type.restore(&mut decoded);
+
+        let decoded = match bitmap {
+            Some(bitmap) => apply_bitmap(&decoded, bitmap, sect3_body.num_points as usize)?,
+            None => decoded,
+        };
+        Ok(decoded)
+    }
+}
+
+fn unpack_jpeg2000_code_stream<T: FloatValue>(
+    input: &[u8],
+    nbit: u8,
+    scaling: &Scaling<T>,
+    expected_len: usize,
+) -> Result<Box<[T]>, Jpeg2000CodeStreamDecodeError> {
+    if let Some(value) = constant_field_if_nbit_zero(nbit, scaling) {
+        return Ok(vec![value; expected_len].into_boxed_slice());
+    }
+    if input.is_empty() {
+        return Err(Jpeg2000CodeStreamDecodeError::LengthMismatch);
+    }
+
+    let encoded = decode_code_stream(input)?;
+    if encoded.len() != expected_len {
+        return Err(Jpeg2000CodeStreamDecodeError::LengthMismatch);
+    }
+    Ok(encoded
+        .iter()
+        .map(|encoded| scaling.apply((*encoded).into()))
+        .collect())
+}
+
+#[cfg(feature = "jpeg2000")]
+fn decode_code_stream(input: &[u8]) -> Result<Vec<i32>, Jpeg2000CodeStreamDecodeError> {
+    crate::jpeg2000::decode(input).ok_or(Jpeg2000CodeStreamDecodeError::CodeStreamDecodeFailed)
+}
+
+#[cfg(not(feature = "jpeg2000"))]
+fn decode_code_stream(_input: &[u8]) -> Result<Vec<i32>, Jpeg2000CodeStreamDecodeError> {
+    Err(Jpeg2000CodeStreamDecodeError::NotSupported)
+}
+
+struct PngDecoder {}
+
+impl<R: Grib2Read> Grib2DataDecode<R> for PngDecoder {
+    fn decode<T: FloatValue>(
+        sect3: &SectionInfo,
+        sect5: &SectionInfo,
+        bitmap: Option<&[u8]>,
+        sect7: &SectionInfo,
+        mut reader: RefMut<R>,
+    ) -> Result<Box<[T]>, GribError> {
+        let (sect3_body, sect5_body) = match (sect3.body.as_ref(), sect5.body.as_ref()) {
+            (Some(SectionBody::Section3(b3)), Some(SectionBody::Section5(b5))) => (b3, b5),
+            _ => return Err(GribError::InternalDataError),
+        };
+
+        let sect5_data = reader.read_sect_body_bytes(sect5)?;
+        if sect5_data.len() < 16 {
+            return Err(GribError::DecodeError(DecodeError::PngDecodeError(
+                PngDecodeError::LengthMismatch,
+            )));
+        }
+        let ref_val = read_as!(f32, sect5_data, 6);
+        let exp = read_as!(u16, sect5_data, 10).into_grib_int();
+        let dig = read_as!(u16, sect5_data, 12).into_grib_int();
+        let nbit = read_as!(u8, sect5_data, 14);
+        let value_type = OriginalValueType::from_code(read_as!(u8, sect5_data, 15)).ok_or(
+            DecodeError::PngDecodeError(PngDecodeError::OriginalFieldValueTypeNotSupported),
+        )?;
+
+        let sect7_data = reader.read_sect_body_bytes(sect7)?;
+
+        let mut decoded = unpack_png(
+            &sect7_data,
+            nbit,
+            ref_val,
+            exp,
+            dig,
+            sect5_body.num_points as usize,
+        )
+        .map_err(DecodeError::PngDecodeError)?;
+        value_type.restore(&mut decoded);
+
+        let decoded = match bitmap {
+            Some(bitmap) => apply_bitmap(&decoded, bitmap, sect3_body.num_points as usize)?,
+            None => decoded,
+        };
+        Ok(decoded)
+    }
+}
+
+/// Decodes a PNG image whose pixels are `nbit`-bit packed integers.
+///
+/// Rows of images with 8, 16, 24 (RGB) and 32 (RGBA) bits per pixel have
+/// no padding, so the raw image data is a sequence of big-endian packed
+/// integers as with simple packing.
+fn unpack_png<T: FloatValue>(
+    input: &[u8],
+    nbit: u8,
+    ref_val: f32,
+    exp: i16,
+    dig: i16,
+    expected_len: usize,
+) -> Result<Box<[T]>, PngDecodeError> {
+    let scaling = Scaling::new(ref_val, exp, dig);
+    if let Some(value) = constant_field_if_nbit_zero(nbit, &scaling) {
+        return Ok(vec![value; expected_len].into_boxed_slice());
+    }
+    if !matches!(nbit, 8 | 16 | 24 | 32) {
+        return Err(PngDecodeError::BitDepthNotSupported(nbit));
+    }
+
+    let mut decoder = png::Decoder::new(input);
+    decoder.set_transformations(png::Transformations::IDENTITY);
+    let mut reader = decoder
+        .read_info()
+        .map_err(|e| PngDecodeError::PngError(e.to_string()))?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buf)
+        .map_err(|e| PngDecodeError::PngError(e.to_string()))?;
+    if info.color_type.samples() * info.bit_depth as usize != usize::from(nbit) {
+        return Err(PngDecodeError::BitDepthMismatch);
+    }
+    if info.width as usize * info.height as usize != expected_len {
+        return Err(PngDecodeError::LengthMismatch);
+    }
+    buf.truncate(info.buffer_size());
+
+    unpack_simple_packing(&buf, nbit, ref_val, exp, dig, Some(expected_len))
+        .map_err(|_| PngDecodeError::LengthMismatch)
+}
+
+struct CcsdsDecoder {}
+
+impl<R: Grib2Read> Grib2DataDecode<R> for CcsdsDecoder {
+    fn decode<T: FloatValue>(
+        sect3: &SectionInfo,
+        sect5: &SectionInfo,
+        bitmap: Option<&[u8]>,
+        sect7: &SectionInfo,
+        mut reader: RefMut<R>,
+    ) -> Result<Box<[T]>, GribError> {
+        let (sect3_body, sect5_body) = match (sect3.body.as_ref(), sect5.body.as_ref()) {
+            (Some(SectionBody::Section3(b3)), Some(SectionBody::Section5(b5))) => (b3, b5),
+            _ => return Err(GribError::InternalDataError),
+        };
+
+        let sect5_data = reader.read_sect_body_bytes(sect5)?;
+        if sect5_data.len() < 20 {
+            return Err(GribError::DecodeError(DecodeError::CcsdsDecodeError(
+                CcsdsDecodeError::LengthMismatch,
+            )));
+        }
+        let ref_val = read_as!(f32, sect5_data, 6);
+        let exp = read_as!(u16, sect5_data, 10).into_grib_int();
+        let dig = read_as!(u16, sect5_data, 12).into_grib_int();
+        let value_type = OriginalValueType::from_code(read_as!(u8, sect5_data, 15)).ok_or(
+            DecodeError::CcsdsDecodeError(CcsdsDecodeError::OriginalFieldValueTypeNotSupported),
+        )?;
+        let param = ccsds::AecParam {
+            bits_per_sample: read_as!(u8, sect5_data, 14),
+            flags: read_as!(u8, sect5_data, 16),
+            block_size: read_as!(u8, sect5_data, 17),
+            rsi: read_as!(u16, sect5_data, 18),
+        };
+
+        let sect7_data = reader.read_sect_body_bytes(sect7)?;
+
+        let mut decoded = unpack_ccsds(
+            &sect7_data,
+            &param,
+            &Scaling::new(ref_val, exp, dig),
+            sect5_body.num_points as usize,
+        )
+        .map_err(DecodeError::CcsdsDecodeError)?;
+        value_type.restore(&mut decoded);
+
+        let decoded = match bitmap {
+            Some(bitmap) => apply_bitmap(&decoded, bitmap, sect3_body.num_points as usize)?,
+            None => decoded,
+        };
+        Ok(decoded)
+    }
+}
+
+fn unpack_ccsds<T: FloatValue>(
+    input: &[u8],
+    param: &ccsds::AecParam,
+    scaling: &Scaling<T>,
+    expected_len: usize,
+) -> Result<Box<[T]>, CcsdsDecodeError> {
+    if let Some(value) = constant_field_if_nbit_zero(param.bits_per_sample, scaling) {
+        return Ok(vec![value; expected_len].into_boxed_slice());
+    }
+
+    let encoded = ccsds::decode(input, param, expected_len)?;
+    Ok(encoded
+        .iter()
+        .map(|encoded| scaling.apply(*encoded))
+        .collect())
+}
+
+struct SpectralSimplePackingDecoder {}
+
+impl<R: Grib2Read> Grib2DataDecode<R> for SpectralSimplePackingDecoder {
+    fn decode<T: FloatValue>(
+        _sect3: &SectionInfo,
+        sect5: &SectionInfo,
+        bitmap: Option<&[u8]>,
+        sect7: &SectionInfo,
+        mut reader: RefMut<R>,
+    ) -> Result<Box<[T]>, GribError> {
+        let sect5_body = match sect5.body.as_ref() {
+            Some(SectionBody::Section5(b5)) => b5,
+            _ => return Err(GribError::InternalDataError),
+        };
+
+        // Bit maps are not applicable to spherical harmonic coefficients.
+        if bitmap.is_some() {
+            return Err(GribError::DecodeError(
+                DecodeError::BitMapIndicatorUnsupported,
+            ));
+        }
+
+        let sect5_data = reader.read_sect_body_bytes(sect5)?;
+        if sect5_data.len() < 19 {
+            return Err(GribError::DecodeError(
+                DecodeError::SpectralPackingDecodeError(SpectralPackingDecodeError::LengthMismatch),
+            ));
+        }
+        let ref_val = read_as!(f32, sect5_data, 6);
+        let exp = read_as!(u16, sect5_data, 10).into_grib_int();
+        let dig = read_as!(u16, sect5_data, 12).into_grib_int();
+        let nbit = read_as!(u8, sect5_data, 14);
+        let real_part_00 = read_as!(f32, sect5_data, 15);
+
+        let sect7_data = reader.read_sect_body_bytes(sect7)?;
+
+        // The real part of the (0,0) coefficient is stored in Section 5
+        // and is not included in the packed values.
+        let num_packed = (sect5_body.num_points as usize).checked_sub(1).ok_or(
+            DecodeError::SpectralPackingDecodeError(SpectralPackingDecodeError::LengthMismatch),
+        )?;
+        let packed = unpack_simple_packing(&sect7_data, nbit, ref_val, exp, dig, Some(num_packed))
+            .map_err(DecodeError::SimplePackingDecodeError)?;
+
+        let mut decoded = Vec::with_capacity(sect5_body.num_points as usize);
+        decoded.push(T::from(real_part_00));
+        decoded.extend_from_slice(&packed);
+        Ok(decoded.into_boxed_slice())
+    }
+}
+
+struct SpectralComplexPackingDecoder {}
+
+impl<R: Grib2Read> Grib2DataDecode<R> for SpectralComplexPackingDecoder {
+    fn decode<T: FloatValue>(
+        sect3: &SectionInfo,
+        sect5: &SectionInfo,
+        bitmap: Option<&[u8]>,
+        sect7: &SectionInfo,
+        mut reader: RefMut<R>,
+    ) -> Result<Box<[T]>, GribError> {
+        let (sect3_body, sect5_body) = match (sect3.body.as_ref(), sect5.body.as_ref()) {
+            (Some(SectionBody::Section3(b3)), Some(SectionBody::Section5(b5))) => (b3, b5),
+            _ => return Err(GribError::InternalDataError),
+        };
+
+        // Bit maps are not applicable to spherical harmonic coefficients.
+        if bitmap.is_some() {
+            return Err(GribError::DecodeError(
+                DecodeError::BitMapIndicatorUnsupported,
+            ));
+        }
+
+        let truncation =
+            sect3_body
+                .spherical_harmonics()
+                .ok_or(DecodeError::SpectralPackingDecodeError(
+                    SpectralPackingDecodeError::NotSpectralGrid,
+                ))?;
+
+        let sect5_data = reader.read_sect_body_bytes(sect5)?;
+        if sect5_data.len() < 30 {
+            return Err(GribError::DecodeError(
+                DecodeError::SpectralPackingDecodeError(SpectralPackingDecodeError::LengthMismatch),
+            ));
+        }
+        let param = SpectralComplexPackingParam {
+            ref_val: read_as!(f32, sect5_data, 6),
+            exp: read_as!(u16, sect5_data, 10).into_grib_int(),
+            dig: read_as!(u16, sect5_data, 12).into_grib_int(),
+            nbit: read_as!(u8, sect5_data, 14),
+            laplacian: read_as!(u32, sect5_data, 15).into_grib_int(),
+            subset: SphericalHarmonics {
+                j: read_as!(u16, sect5_data, 19).into(),
+                k: read_as!(u16, sect5_data, 21).into(),
+                m: read_as!(u16, sect5_data, 23).into(),
+                repr_type: truncation.repr_type,
+                repr_mode: truncation.repr_mode,
+            },
+            num_unpacked: read_as!(u32, sect5_data, 25) as usize,
+            unpacked_precision: read_as!(u8, sect5_data, 29),
+        };
+
+        let sect7_data = reader.read_sect_body_bytes(sect7)?;
+
+        let decoded = unpack_spectral_complex_packing(
+            &sect7_data,
+            &truncation,
+            &param,
+            Some(sect5_body.num_points as usize),
+        )
+        .map_err(DecodeError::SpectralPackingDecodeError)?;
+        Ok(decoded)
+    }
+}
+
+struct SpectralComplexPackingParam {
+    ref_val: f32,
+    exp: i16,
+    dig: i16,
+    nbit: u8,
+    /// Laplacian scaling factor expressed in 10^-6 units
+    laplacian: i32,
+    /// Truncation of the subset of coefficients stored without packing
+    subset: SphericalHarmonics,
+    num_unpacked: usize,
+    /// Precision of the unpacked subset (see Code Table 5.7)
+    unpacked_precision: u8,
+}
+
+fn unpack_spectral_complex_packing<T: FloatValue>(
+    input: &[u8],
+    truncation: &SphericalHarmonics,
+    param: &SpectralComplexPackingParam,
+    expected_len: Option<usize>,
+) -> Result<Box<[T]>, SpectralPackingDecodeError> {
+    let unpacked_size = match param.unpacked_precision {
+        1 => std::mem::size_of::<f32>(),
+        2 => std::mem::size_of::<f64>(),
+        p => return Err(SpectralPackingDecodeError::UnpackedSubsetPrecisionNotSupported(p)),
+    };
+    let packed_start = param.num_unpacked * unpacked_size;
+    if packed_start > input.len() {
+        return Err(SpectralPackingDecodeError::LengthMismatch);
+    }
+
+    let read_unpacked = |i: usize| -> T {
+        let pos = i * unpacked_size;
+        match param.unpacked_precision {
+            1 => T::from(read_as!(f32, input, pos)),
+            _ => T::from_f64(read_as!(f64, input, pos)),
+        }
+    };
+
+    let num_values = truncation.num_coefficients() * 2;
+    let num_packed = num_values
+        .checked_sub(param.num_unpacked)
+        .ok_or(SpectralPackingDecodeError::LengthMismatch)?;
+    let packed: Box<[T]> = unpack_simple_packing(
+        &input[packed_start..],
+        param.nbit,
+        param.ref_val,
+        param.exp,
+        param.dig,
+        Some(num_packed),
+    )
+    .map_err(SpectralPackingDecodeError::SimplePackingDecodeError)?;
+
+    let laplacian = f64::from(param.laplacian) * 1e-6;
+    let mut out_buf = Vec::with_capacity(num_values);
+    let mut unpacked_iter = 0..param.num_unpacked;
+    let mut packed_iter = packed.iter();
+
+    for m in 0..=truncation.m {
+        for n in m..=truncation.max_n(m) {
+            if m <= param.subset.m && n <= param.subset.max_n(m) {
+                for _ in 0..2 {
+                    let i = unpacked_iter
+                        .next()
+                        .ok_or(SpectralPackingDecodeError::LengthMismatch)?;
+                    out_buf.push(read_unpacked(i));
+                }
+            } else {
+                let n = f64::from(n);
+                let factor = (n * (n + 1.)).powf(-laplacian);
+                for _ in 0..2 {
+                    let value = packed_iter
+                        .next()
+                        .ok_or(SpectralPackingDecodeError::LengthMismatch)?;
+                    out_buf.push(T::from_f64(value.to_f64() * factor));
+                }
+            }
+        }
+    }
+
+    if let Some(len) = expected_len {
+        if len != out_buf.len() {
+            return Err(SpectralPackingDecodeError::LengthMismatch);
+        }
+    }
+
+    Ok(out_buf.into_boxed_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // This test data is based on 4-bit numbers of sample data
+    // provided in JMBSC's document available in the following URL:
+    // http://www.jmbsc.or.jp/jp/online/joho-sample/Run-Length_Encoding.pdf
+    #[test]
+    fn rleunpack_u8() {
+        let input: Vec<u8> = vec![3, 9, 12, 6, 4, 15, 2, 1, 0, 13, 12, 2, 3];
+        let output: Vec<u8> = vec![
+            3, 9, 9, 6, 4, 4, 4, 4, 4, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 2, 3,
+        ];
+        let input: Vec<u8> = input.iter().map(|n| n + 240).collect();
+        let output: Vec<u16> = output.iter().map(|n| u16::from(*n) + 240).collect();
+
+        assert_eq!(
+            rleunpack(&input, 8, 250, Some(21)),
+            Ok(output.into_boxed_slice())
+        );
+    }
+
+    #[test]
+    fn rleunpack_u8_long_length() {
+        let input: Vec<u8> = vec![0x00, 0x14, 0x1c];
+        let output: Vec<u16> = vec![0; 6065];
+
+        assert_eq!(rleunpack(&input, 8, 3, None), Ok(output.into_boxed_slice()));
+    }
+
+    // This is the 4-bit sample data in the document for the test above.
+    #[test]
+    fn rleunpack_4_bits() {
+        let input: Vec<u8> = vec![0x39, 0xc6, 0x4f, 0x21, 0x0d, 0xc2, 0x30];
+        let output: Vec<u16> = vec![
+            3, 9, 9, 6, 4, 4, 4, 4, 4, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 2, 3,
+        ];
+
+        assert_eq!(
+            rleunpack(&input, 4, 10, Some(21)),
+            Ok(output.into_boxed_slice())
+        );
+    }
+
+    #[test]
+    fn rleunpack_16_bits_and_bit_widths_not_byte_aligned() {
+        // levels 1000 and 2 with runs of 3 and 1 + 2 * (65536 - 1001)
+        let input: Vec<u8> = vec![0x03, 0xe8, 0x03, 0xeb, 0x00, 0x02, 0x03, 0xe9, 0x03, 0xeb];
+        let mut output = vec![1000; 3];
+        output.extend_from_slice(&vec![2; 1 + 2 * (65536 - 1001)]);
+        assert_eq!(
+            rleunpack(&input, 16, 1000, None),
+            Ok(output.into_boxed_slice())
+        );
+
+        // 3-bit levels 1, 2 and 0 with a run of 2 followed by padding
+        let input: Vec<u8> = vec![0b00101000, 0b01100000];
+        assert_eq!(
+            rleunpack(&input, 3, 4, Some(4)),
+            Ok(vec![1, 2, 0, 0].into_boxed_slice())
+        );
+    }
+
+    #[test]
+    fn rleunpack_with_long_runs() {
+        // a level followed by many run-length codes, the last of which is
+        // a digit with a place value beyond `usize`
+        let mut input = vec![0x00, 0x00];
+        for _ in 0..8 {
+            input.extend_from_slice(&[0x00, 0x02]);
+        }
+        input.extend_from_slice(&[0x00, 0x03]);
+        assert_eq!(
+            rleunpack(&input, 16, 1, None),
+            Err(RunLengthEncodingDecodeError::LengthMismatch)
+        );
+
+        // a run of 65534 * 65534 values longer than expected
+        let input = vec![0x00, 0x00, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(
+            rleunpack(&input, 16, 1, Some(100)),
+            Err(RunLengthEncodingDecodeError::LengthMismatch)
+        );
+    }
+
+    #[test]
+    fn rleunpack_with_invalid_parameters() {
+        assert_eq!(
+            rleunpack(&[0], 17, 0, None),
+            Err(RunLengthEncodingDecodeError::NotSupported)
+        );
+        assert_eq!(
+            rleunpack(&[0], 4, 16, None),
+            Err(RunLengthEncodingDecodeError::InvalidLevelValue(16))
+        );
+        assert_eq!(
+            rleunpack(&[0xf0], 4, 10, None),
+            Err(RunLengthEncodingDecodeError::InvalidFirstValue)
+        );
+    }
+
+    #[test]
+    fn simple_packing_u8() {
+        let ref_val_bytes = [0x35, 0x3e, 0x6b, 0xf6];
+        let exp = 0x801a;
+        let dig = 0x0000;
+        let input: Vec<u8> = vec![0x00, 0x06, 0x00, 0x0d];
+        let expected: Vec<f32> = vec![7.987_831_6e-7, 9.030_913e-7];
+
+        let ref_val = f32::from_be_bytes(ref_val_bytes[..].try_into().unwrap());
+        let actual = unpack_simple_packing::<f32>(
+            &input,
+            16,
+            ref_val,
+            exp.into_grib_int(),
+            dig.into_grib_int(),
+            Some(2),
+        )
+        .unwrap();
+
+        assert_eq!(actual.len(), expected.len());
+        let mut i = 0;
+        while i < actual.len() {
+            assert!(actual[i] < expected[i] + 0.00000001);
+            assert!(actual[i] > expected[i] - 0.00000001);
+            i += 1;
+        }
+    }
+
+    #[test]
+    fn simple_packing_of_bit_widths_not_byte_aligned() {
+        // 12-bit values 1, 4095, 2048, 0 and 3-bit padding
+        let input = vec![0x00, 0x1f, 0xff, 0x80, 0x00, 0x00];
+
+        assert_eq!(
+            unpack_simple_packing::<f32>(&input, 12, 1., 1, 0, Some(4)),
+            Ok(vec![3., 8191., 4097., 1.].into_boxed_slice())
+        );
+    }
+
+    #[test]
+    fn simple_packing_of_1_and_32_bits() {
+        assert_eq!(
+            unpack_simple_packing::<f32>(&[0b10110000], 1, 0., 0, 0, Some(5)),
+            Ok(vec![1., 0., 1., 1., 0.].into_boxed_slice())
+        );
+        assert_eq!(
+            unpack_simple_packing::<f32>(&[0x00, 0x01, 0x00, 0x00], 32, -1., 0, 0, None),
+            Ok(vec![65535.].into_boxed_slice())
+        );
+    }
+
+    #[test]
+    fn simple_packing_with_unsupported_or_short_input() {
+        assert_eq!(
+            unpack_simple_packing::<f32>(&[0x00; 4], 33, 0., 0, 0, Some(1)),
+            Err(SimplePackingDecodeError::NotSupported)
+        );
+        assert_eq!(
+            unpack_simple_packing::<f32>(&[0x00; 4], 12, 0., 0, 0, Some(3)),
+            Err(SimplePackingDecodeError::LengthMismatch)
+        );
+    }
+
+    #[test]
+    fn simple_packing_of_constant_field() {
+        assert_eq!(
+            unpack_simple_packing::<f32>(&[], 0, 1.5, 0, 0, Some(3)),
+            Ok(vec![1.5, 1.5, 1.5].into_boxed_slice())
+        );
+        assert_eq!(
+            unpack_simple_packing::<f32>(&[], 0, 1.5, 0, 0, None),
+            Ok(Vec::new().into_boxed_slice())
+        );
+        assert_eq!(
+            unpack_simple_packing::<f32>(&[], 12, 1.5, 0, 0, Some(2)),
+            Err(SimplePackingDecodeError::LengthMismatch)
+        );
+    }
+
+    #[test]
+    fn jpeg2000_code_stream_of_constant_field() {
+        let scaling = Scaling::<f32>::new(1.5, 0, 1);
+        assert_eq!(
+            unpack_jpeg2000_code_stream::<f32>(&[], 8, &scaling, 2),
+            Err(Jpeg2000CodeStreamDecodeError::LengthMismatch)
+        );
+        assert_eq!(
+            unpack_jpeg2000_code_stream::<f32>(&[0xff, 0x4f], 0, &scaling, 1),
+            Ok(vec![0.15].into_boxed_slice())
+        );
+    }
+
+    #[cfg(not(feature = "jpeg2000"))]
+    #[test]
+    fn jpeg2000_code_stream_without_feature() {
+        assert_eq!(
+            unpack_jpeg2000_code_stream::<f32>(&[0xff, 0x4f], 8, &Scaling::<f32>::new(0., 0, 0), 1),
+            Err(Jpeg2000CodeStreamDecodeError::NotSupported)
+        );
+    }
+
+    fn encode_png(
+        width: u32,
+        height: u32,
+        color_type: png::ColorType,
+        bit_depth: png::BitDepth,
+        data: &[u8],
+    ) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let mut encoder = png::Encoder::new(&mut buf, width, height);
+        encoder.set_color(color_type);
+        encoder.set_depth(bit_depth);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(data).unwrap();
+        writer.finish().unwrap();
+        buf
+    }
+
+    #[test]
+    fn png_of_8_16_24_and_32_bits() {
+        let cases = [
+            (png::ColorType::Grayscale, png::BitDepth::Eight, 8),
+            (png::ColorType::Grayscale, png::BitDepth::Sixteen, 16),
+            (png::ColorType::Rgb, png::BitDepth::Eight, 24),
+            (png::ColorType::Rgba, png::BitDepth::Eight, 32),
+        ];
+        for (color_type, bit_depth, nbit) in cases.iter() {
+            let nbyte = nbit / 8;
+            // 3x2 pixels with values 0, 1, 2, 3, 4 and 2^nbit - 1
+            let mut data = Vec::new();
+            for value in [0_u32, 1, 2, 3, 4, u32::MAX >> (32 - nbit)].iter() {
+                data.extend_from_slice(&value.to_be_bytes()[(4 - nbyte)..]);
+            }
+            let input = encode_png(3, 2, *color_type, *bit_depth, &data);
+            let max = (u32::MAX >> (32 - nbit)) as f32;
+
+            assert_eq!(
+                unpack_png::<f32>(&input, *nbit as u8, 1., 1, 0, 6),
+                Ok(vec![1., 3., 5., 7., 9., 1. + max * 2.].into_boxed_slice())
+            );
+        }
+    }
+
+    #[test]
+    fn png_with_unsupported_or_inconsistent_input() {
+        let input = encode_png(
+            2,
+            1,
+            png::ColorType::Grayscale,
+            png::BitDepth::Sixteen,
+            &[0, 1, 0, 2],
+        );
+        assert_eq!(
+            unpack_png::<f32>(&input, 12, 0., 0, 0, 2),
+            Err(PngDecodeError::BitDepthNotSupported(12))
+        );
+        assert_eq!(
+            unpack_png::<f32>(&input, 8, 0., 0, 0, 2),
+            Err(PngDecodeError::BitDepthMismatch)
+        );
+        assert_eq!(
+            unpack_png::<f32>(&input, 16, 0., 0, 0, 3),
+            Err(PngDecodeError::LengthMismatch)
+        );
+        assert!(matches!(
+            unpack_png::<f32>(&input[..8], 16, 0., 0, 0, 2),
+            Err(PngDecodeError::PngError(_))
+        ));
+    }
+
+    #[test]
+    fn png_of_constant_field() {
+        assert_eq!(
+            unpack_png::<f32>(&[], 0, 1.5, 0, 0, 2),
+            Ok(vec![1.5, 1.5].into_boxed_slice())
+        );
+        assert!(matches!(
+            unpack_png::<f32>(&[], 16, 1.5, 0, 0, 2),
+            Err(PngDecodeError::PngError(_))
+        ));
+    }
+
+    #[test]
+    fn ieee_float_of_32_and_64_bits() {
+        let mut input = Vec::new();
+        input.extend_from_slice(&1.5_f32.to_be_bytes());
+        input.extend_from_slice(&(-0.1_f32).to_be_bytes());
+        assert_eq!(
+            unpack_ieee_float::<f64>(&input, 1, 2),
+            Ok(vec![1.5, f64::from(-0.1_f32)].into_boxed_slice())
+        );
+
+        let mut input = Vec::new();
+        input.extend_from_slice(&1.5_f64.to_be_bytes());
+        input.extend_from_slice(&(-0.1_f64).to_be_bytes());
+        assert_eq!(
+            unpack_ieee_float::<f64>(&input, 2, 2),
+            Ok(vec![1.5, -0.1].into_boxed_slice())
+        );
+    }
+
+    #[test]
+    fn ieee_float_with_unsupported_precision_or_short_input() {
+        assert_eq!(
+            unpack_ieee_float::<f64>(&[0; 16], 3, 1),
+            Err(IeeeFloatDecodeError::PrecisionNotSupported(3))
+        );
+        assert_eq!(
+            unpack_ieee_float::<f64>(&[0; 12], 2, 2),
+            Err(IeeeFloatDecodeError::LengthMismatch)
+        );
+    }
+
+    #[test]
+    fn simple_packing_param_of_templates_5_0_1_and_61() {
+        // octets 6-21 of Template 5.0 with R = 1.5, E = -1, D = 2 and 12
+        // bits per value
+        let mut buf = vec![0, 0, 0, 4, 0, 0, 0x3f, 0xc0, 0, 0, 0x80, 1, 0, 2, 12, 0];
+        assert_eq!(
+            SimplePackingParam::from_bytes(0, &buf),
+            Ok(SimplePackingParam {
+                ref_val: 1.5,
+                exp: -1,
+                dig: 2,
+                nbit: 12,
+                value_type: OriginalValueType::FloatingPoint,
+                variant: SimplePackingVariant::Plain,
+            })
+        );
+
+        let mut matrix = buf.clone();
+        matrix[4..6].copy_from_slice(&[0, 1]);
+        // no matrix bit maps, 24 values and 2 x 3 matrices followed by
+        // coordinate definitions
+        matrix.extend_from_slice(&[255, 0, 0, 0, 24, 0, 2, 0, 3, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            SimplePackingParam::from_bytes(1, &matrix).map(|param| param.variant),
+            Ok(SimplePackingVariant::Matrix {
+                num_values: 24,
+                num_rows: 2,
+                num_cols: 3,
+            })
+        );
+        // Matrix bit maps are present or predefined.
+        for indicator in [0, 1, 254] {
+            matrix[16] = indicator;
+            assert_eq!(
+                SimplePackingParam::from_bytes(1, &matrix),
+                Err(SimplePackingDecodeError::MatrixBitmapsNotSupported)
+            );
+        }
+
+        // The pre-processing parameter of Template 5.61 is 0.5.
+        buf[4..6].copy_from_slice(&[0, 61]);
+        buf.truncate(15);
+        buf.extend_from_slice(&0.5_f32.to_be_bytes());
+        assert_eq!(
+            SimplePackingParam::from_bytes(61, &buf).map(|param| param.variant),
+            Ok(SimplePackingVariant::Logarithmic(0.5))
+        );
+        assert_eq!(
+            SimplePackingParam::from_bytes(61, &buf[..18]),
+            Err(SimplePackingDecodeError::LengthMismatch)
+        );
+    }
+
+    #[test]
+    fn simple_packing_param_with_integer_values() {
+        let mut buf = vec![0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 8, 1];
+        assert_eq!(
+            SimplePackingParam::from_bytes(0, &buf).map(|param| param.value_type),
+            Ok(OriginalValueType::Integer)
+        );
+
+        buf[15] = 2;
+        assert_eq!(
+            SimplePackingParam::from_bytes(0, &buf),
+            Err(SimplePackingDecodeError::OriginalFieldValueTypeNotSupported)
+        );
+    }
+
+    #[test]
+    fn missing_values_replaced() {
+        let mut values = [1_f32, f32::NAN, -2.];
+        MissingValue::Value(9.999e20).apply(&mut values);
+        assert_eq!(values, [1., 9.999e20, -2.]);
+
+        let mut values = [1_f32, f32::NAN];
+        MissingValue::Nan.apply(&mut values);
+        assert_eq!(values[0], 1.);
+        assert!(values[1].is_nan());
+    }
+
+    #[test]
+    fn integer_values_rounded() {
+        let values = [0.99999994_f32, 2.0000002, -3.0000002, f32::NAN];
+        let mut actual = values;
+        OriginalValueType::Integer.restore(&mut actual);
+        assert_eq!(actual[..3], [1., 2., -3.]);
+        assert!(actual[3].is_nan());
+
+        let mut actual = values;
+        OriginalValueType::FloatingPoint.restore(&mut actual);
+        assert_eq!(actual[..3], [0.99999994, 2.0000002, -3.0000002]);
+    }
+
+    #[test]
+    fn integer_values_converted_into_integer_types() {
+        let values = [1., -2., f64::NAN, 4_294_967_295.];
+
+        assert_eq!(
+            to_integers::<i32>(&values[..3]),
+            Ok(vec![Some(1), Some(-2), None].into_boxed_slice())
+        );
+        assert_eq!(
+            to_integers::<i32>(&values),
+            Err(DecodeError::IntegerValueOutOfRange)
+        );
+        assert_eq!(
+            to_integers::<u32>(&[values[0], values[2], values[3]]),
+            Ok(vec![Some(1), None, Some(u32::MAX)].into_boxed_slice())
+        );
+        assert_eq!(
+            to_integers::<u32>(&values),
+            Err(DecodeError::IntegerValueOutOfRange)
+        );
+    }
+
+    #[test]
+    fn logarithmic_preprocessing_reversed() {
+        let mut values = [0_f32, 1., 2_f32.ln()];
+        reverse_logarithmic_preprocessing(&mut values, 1.);
+        assert_eq!(values, [0., 1_f32.exp() - 1., 1.]);
+    }
+
+    #[test]
+    fn bitmap_repeated_for_matrices() {
+        assert_eq!(
+            repeat_bits(&[0b10100000], 3, 3),
+            vec![0b11100011, 0b10000000].into_boxed_slice()
+        );
+    }
+
+    #[test]
+    fn bitmap_applied_to_values() {
+        let actual = apply_bitmap(&[1_f32, 2., 3.], &[0b10010100], 7).unwrap();
+
+        assert_eq!(actual.len(), 7);
+        assert_eq!(actual[0], 1.);
+        assert_eq!(actual[3], 2.);
+        assert_eq!(actual[5], 3.);
+        for i in [1, 2, 4, 6].iter() {
+            assert!(actual[*i].is_nan());
+        }
+    }
+
+    #[test]
+    fn bitmap_with_wrong_number_of_values() {
+        assert_eq!(
+            apply_bitmap(&[1., 2.], &[0b10010100], 7),
+            Err(DecodeError::BitMapLengthMismatch)
+        );
+        assert_eq!(
+            apply_bitmap(&[1., 2., 3., 4.], &[0b10010100], 7),
+            Err(DecodeError::BitMapLengthMismatch)
+        );
+        assert_eq!(
+            apply_bitmap(&[1.], &[0b10000000], 9),
+            Err(DecodeError::BitMapLengthMismatch)
+        );
+    }
+
+    fn complex_packing_param(num_groups: usize, missing_management: u8) -> ComplexPackingParam {
+        ComplexPackingParam {
+            ref_val: 0.,
+            exp: 0,
+            dig: 0,
+            group_ref_nbit: 4,
+            value_type: OriginalValueType::FloatingPoint,
+            group_splitting: 1,
+            missing_management,
+            num_groups,
+            group_width_ref: 0,
+            group_width_nbit: 4,
+            group_len_ref: 2,
+            group_len_inc: 1,
+            group_len_last: 1,
+            group_len_nbit: 4,
+            spatial_differencing: None,
+        }
+    }
+
+    #[test]
+    fn complex_packing_with_missing_groups() {
+        // group references 3, 15 and 14, group widths 2, 0 and 0,
+        // scaled group lengths 1, 0 and (unused) 0, and values of the
+        // first group 0, 3 and 1
+        let input = vec![0x3f, 0xe0, 0x20, 0x00, 0x10, 0x00, 0x34];
+        let param = complex_packing_param(3, 2);
+
+        let actual = unpack_complex_packing::<f32>(&input, &param, 6).unwrap();
+
+        assert_eq!(actual[0], 3.);
+        assert!(actual[1].is_nan());
+        assert_eq!(actual[2], 4.);
+        assert!(actual[3..].iter().all(|v| v.is_nan()));
+    }
+
+    #[test]
+    fn complex_packing_with_unsupported_or_short_input() {
+        let input = vec![0x3f, 0xe0, 0x20, 0x00, 0x10, 0x00, 0x34];
+
+        assert_eq!(
+            unpack_complex_packing::<f32>(&input, &complex_packing_param(3, 2), 7),
+            Err(ComplexPackingDecodeError::LengthMismatch)
+        );
+        assert_eq!(
+            unpack_complex_packing::<f32>(&input[..6], &complex_packing_param(3, 2), 6),
+            Err(ComplexPackingDecodeError::LengthMismatch)
+        );
+        assert_eq!(
+            unpack_complex_packing::<f32>(&input, &complex_packing_param(3, 3), 6),
+            Err(ComplexPackingDecodeError::MissingValueManagementNotSupported(3))
+        );
+    }
+
+    #[test]
+    fn extra_descriptors_read_as_sign_magnitude() {
+        let input = vec![0x01, 0x02, 0x00, 0x05, 0x80, 0x03];
+        let param = SpatialDifferencingParam {
+            order: 2,
+            num_octets: 2,
+        };
+        let mut reader = BitReader::new(&input);
+
+        assert_eq!(
+            read_extra_descriptors(&mut reader, &param),
+            Ok(([258, 5], -3))
+        );
+
+        // The first values are sign-magnitude integers as well.
+        let input = vec![0x81, 0x02, 0x00, 0x05, 0x00, 0x03];
+        let mut reader = BitReader::new(&input);
+        assert_eq!(
+            read_extra_descriptors(&mut reader, &param),
+            Ok(([-258, 5], 3))
+        );
+    }
+
+    #[test]
+    fn spatial_differencing_reversed_over_non_missing_values() {
+        use GroupedValue::*;
+
+        // original values 10, 12, 15, 19 with second-order differences
+        // 1 and 1, and the minimum difference 1
+        let mut values = vec![Value(0), PrimaryMissing, Value(0), Value(0), Value(0)];
+        reverse_spatial_differencing(&mut values, &[10, 12], 1);
+        assert_eq!(
+            values,
+            vec![Value(10), PrimaryMissing, Value(12), Value(15), Value(19)]
+        );
+
+        let mut values = vec![Value(0), Value(2), SecondaryMissing, Value(0)];
+        reverse_spatial_differencing(&mut values, &[10], -1);
+        assert_eq!(
+            values,
+            vec![Value(10), Value(11), SecondaryMissing, Value(10)]
+        );
+    }
+
+    #[test]
+    fn spectral_complex_packing_with_unpacked_subset() {
+        let truncation = SphericalHarmonics {
+            j: 2,
+            k: 2,
+            m: 2,
+            repr_type: 1,
+            repr_mode: 1,
+        };
+        let param = SpectralComplexPackingParam {
+            ref_val: 0.,
+            exp: 0,
+            dig: 0,
+            nbit: 16,
+            laplacian: 1_000_000,
+            subset: SphericalHarmonics {
+                j: 1,
+                k: 1,
+                m: 1,
+                repr_type: 1,
+                repr_mode: 1,
+            },
+            num_unpacked: 6,
+            unpacked_precision: 1,
+        };
+        let mut input = Vec::new();
+        for value in [10_f32, 0., 20., 30., 40., 50.].iter() {
+            input.extend_from_slice(&value.to_be_bytes());
+        }
+        for value in [6_u16, 12, 18, 24, 30, 36].iter() {
+            input.extend_from_slice(&value.to_be_bytes());
+        }
+        let expected = vec![
+            10., 0., 20., 30., 1., 2., // m = 0
+            40., 50., 3., 4., // m = 1
+            5., 6., // m = 2
+        ];
+
+        assert_eq!(
+            unpack_spectral_complex_packing(&input, &truncation, &param, Some(12)),
+            Ok(expected.into_boxed_slice())
+        );
+
+        assert_eq!(
+            unpack_spectral_complex_packing::<f32>(&input[..34], &truncation, &param, Some(12)),
+            Err(SpectralPackingDecodeError::SimplePackingDecodeError(
+                SimplePackingDecodeError::LengthMismatch
+            ))
+        );
+        let param = SpectralComplexPackingParam { nbit: 33, ..param };
+        assert_eq!(
+            unpack_spectral_complex_packing::<f32>(&input, &truncation, &param, Some(12)),
+            Err(SpectralPackingDecodeError::SimplePackingDecodeError(
+                SimplePackingDecodeError::NotSupported
+            ))
+        );
+    }
 }
@@ -0,0 +1,127 @@
+//! Decoding of JPEG 2000 code streams using OpenJPEG.
+//!
+//! This module uses the OpenJPEG bindings of the `openjpeg-sys` crate,
+//! which builds OpenJPEG from source, and is only built when the
+//! `jpeg2000` feature is enabled.
+
+use openjpeg_sys as opj;
+use std::os::raw::c_void;
+use std::ptr;
+
+/// Input stream reading a code stream from memory.
+struct MemoryStream<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+unsafe extern "C" fn read_stream(
+    buffer: *mut c_void,
+    nb_bytes: usize,
+    user_data: *mut c_void,
+) -> usize {
+    let stream = unsafe { &mut *(user_data as *mut MemoryStream) };
+    let remaining = stream.buf.len() - stream.pos;
+    if remaining == 0 {
+        return usize::MAX;
+    }
+    let len = nb_bytes.min(remaining);
+    unsafe {
+        ptr::copy_nonoverlapping(stream.buf[stream.pos..].as_ptr(), buffer as *mut u8, len);
+    }
+    stream.pos += len;
+    len
+}
+
+unsafe extern "C" fn skip_stream(nb_bytes: i64, user_data: *mut c_void) -> i64 {
+    let stream = unsafe { &mut *(user_data as *mut MemoryStream) };
+    let pos = (stream.pos as i64 + nb_bytes).clamp(0, stream.buf.len() as i64);
+    let skipped = pos - stream.pos as i64;
+    stream.pos = pos as usize;
+    skipped
+}
+
+unsafe extern "C" fn seek_stream(nb_bytes: i64, user_data: *mut c_void) -> opj::OPJ_BOOL {
+    let stream = unsafe { &mut *(user_data as *mut MemoryStream) };
+    if nb_bytes < 0 || nb_bytes as usize > stream.buf.len() {
+        return 0;
+    }
+    stream.pos = nb_bytes as usize;
+    1
+}
+
+/// Owner of OpenJPEG objects destroying them when dropped.
+struct Decompressor {
+    codec: *mut opj::opj_codec_t,
+    stream: *mut opj::opj_stream_t,
+    image: *mut opj::opj_image_t,
+}
+
+impl Drop for Decompressor {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.image.is_null() {
+                opj::opj_image_destroy(self.image);
+            }
+            if !self.stream.is_null() {
+                opj::opj_stream_destroy(self.stream);
+            }
+            if !self.codec.is_null() {
+                opj::opj_destroy_codec(self.codec);
+            }
+        }
+    }
+}
+
+/// Decodes a JPEG 2000 code stream containing a single-component image
+/// and returns its samples in row-major order.
+///
+/// Returns `None` if OpenJPEG fails to decode the code stream or the
+/// image has more than one component.
+pub(crate) fn decode(input: &[u8]) -> Option<Vec<i32>> {
+    let mut memory_stream = MemoryStream { buf: input, pos: 0 };
+
+    unsafe {
+        let mut d = Decompressor {
+            codec: opj::opj_create_decompress(opj::CODEC_FORMAT::OPJ_CODEC_J2K),
+            stream: opj::opj_stream_create(input.len(), 1),
+            image: ptr::null_mut(),
+        };
+        if d.codec.is_null() || d.stream.is_null() {
+            return None;
+        }
+
+        let mut parameters = std::mem::MaybeUninit::<opj::opj_dparameters_t>::zeroed();
+        opj::opj_set_default_decoder_parameters(parameters.as_mut_ptr());
+        if opj::opj_setup_decoder(d.codec, parameters.as_mut_ptr()) == 0 {
+            return None;
+        }
+
+        opj::opj_stream_set_read_function(d.stream, Some(read_stream));
+        opj::opj_stream_set_skip_function(d.stream, Some(skip_stream));
+        opj::opj_stream_set_seek_function(d.stream, Some(seek_stream));
+        opj::opj_stream_set_user_data(
+            d.stream,
+            &mut memory_stream as *mut MemoryStream as *mut c_void,
+            None,
+        );
+        opj::opj_stream_set_user_data_length(d.stream, input.len() as u64);
+
+        if opj::opj_read_header(d.stream, d.codec, &mut d.image) == 0
+            || opj::opj_decode(d.codec, d.stream, d.image) == 0
+            || opj::opj_end_decompress(d.codec, d.stream) == 0
+        {
+            return None;
+        }
+
+        let image = &*d.image;
+        if image.numcomps != 1 {
+            return None;
+        }
+        let comp = &*image.comps;
+        let len = comp.w as usize * comp.h as usize;
+        if comp.data.is_null() {
+            return None;
+        }
+        Some(std::slice::from_raw_parts(comp.data, len).to_vec())
+    }
+}
@@ -1,5 +1,10 @@
+mod ccsds;
 pub mod codetables;
 pub mod context;
 pub mod decoder;
+#[cfg(feature = "jpeg2000")]
+mod jpeg2000;
+pub mod products;
 pub mod reader;
+pub mod spectral;
 mod utils;
@@ -0,0 +1,1322 @@
+use chrono::{offset::TimeZone, DateTime, Duration, Months, Utc};
+use std::convert::TryInto;
+use std::fmt::{self, Display, Formatter};
+
+use crate::codetables::{lookup_constituent, lookup_surface};
+use crate::utils::{read_as, GribInt};
+
+/// Contents of Product Definition Template 4.0 "Analysis or forecast at
+/// a horizontal level or in a horizontal layer at a point in time",
+/// which are also the leading part of many other Product Definition
+/// Templates.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnalysisOrForecast {
+    /// Parameter category (see Code Table 4.1)
+    pub parameter_category: u8,
+    /// Parameter number (see Code Table 4.2)
+    pub parameter_number: u8,
+    /// Type of generating process (see Code Table 4.3)
+    pub generating_process: u8,
+    /// Background generating process identifier (defined by
+    /// originating centre)
+    pub background_process_id: u8,
+    /// Analysis or forecast generating process identifier (defined by
+    /// originating centre)
+    pub forecast_process_id: u8,
+    /// Hours of observational data cutoff after reference time
+    pub cutoff_hours: u16,
+    /// Minutes of observational data cutoff after reference time
+    pub cutoff_minutes: u8,
+    /// Forecast time
+    pub forecast_time: ForecastTime,
+    /// First fixed surface
+    pub first_surface: FixedSurface,
+    /// Second fixed surface
+    pub second_surface: FixedSurface,
+}
+
+impl AnalysisOrForecast {
+    /// Size of the template in octets (octet 10-34)
+    pub(crate) const SIZE: usize = 25;
+
+    /// Parses octets from octet 10 of Section 4.
+    pub(crate) fn from_bytes(buf: &[u8]) -> Option<Self> {
+        if buf.len() < Self::SIZE {
+            return None;
+        }
+
+        Some(Self {
+            parameter_category: buf[0],
+            parameter_number: buf[1],
+            generating_process: buf[2],
+            background_process_id: buf[3],
+            forecast_process_id: buf[4],
+            cutoff_hours: read_as!(u16, buf, 5),
+            cutoff_minutes: buf[7],
+            forecast_time: ForecastTime {
+                unit: buf[8],
+                value: read_as!(u32, buf, 9),
+            },
+            first_surface: FixedSurface::from_bytes(&buf[13..19]),
+            second_surface: FixedSurface::from_bytes(&buf[19..25]),
+        })
+    }
+
+    /// Returns the level or the layer defined with the fixed surfaces,
+    /// or `None` if the first fixed surface is missing.
+    pub fn level_or_layer(&self) -> Option<LevelOrLayer> {
+        let first = self.first_surface.level()?;
+        match self.second_surface.level() {
+            Some(second) => Some(LevelOrLayer::Layer(Layer { first, second })),
+            None => Some(LevelOrLayer::Level(first)),
+        }
+    }
+
+    /// Parses octets from octet 10 of Section 4 of templates such as
+    /// 4.40 and 4.44, which insert `len` octets between the parameter
+    /// number and the type of generating process.
+    pub(crate) fn from_bytes_with_insertion(buf: &[u8], len: usize) -> Option<Self> {
+        let rest = buf.get(2 + len..)?;
+        let buf: Vec<u8> = buf[..2].iter().chain(rest).copied().collect();
+        Self::from_bytes(&buf)
+    }
+}
+
+/// Forecast time in units of time defined in Code Table 4.4
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ForecastTime {
+    /// Indicator of unit of time range (see Code Table 4.4)
+    pub unit: u8,
+    /// Forecast time in units defined by `unit`
+    pub value: u32,
+}
+
+impl ForecastTime {
+    /// Returns the forecast time as a typed lead time.
+    pub fn lead_time(&self) -> Option<LeadTime> {
+        LeadTime::new(self.unit, self.value.into())
+    }
+
+    /// Returns the time obtained by adding the forecast time to the
+    /// reference time `ref_time`.
+    pub fn valid_time(&self, ref_time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.lead_time()?.add_to(ref_time)
+    }
+}
+
+impl Display for ForecastTime {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let value = u64::from(self.value);
+        let (value, unit) = match self.unit {
+            0 => (value, "min"),
+            1 => (value, "hour"),
+            2 => (value, "day"),
+            3 => (value, "month"),
+            4 => (value, "year"),
+            5 => (value, "decade"),
+            6 => (value, "normal"),
+            7 => (value, "century"),
+            10 => (value * 3, "hour"),
+            11 => (value * 6, "hour"),
+            12 => (value * 12, "hour"),
+            13 => (value, "sec"),
+            n => return write!(f, "{} (unit {})", self.value, n),
+        };
+        write!(f, "{} {}", value, unit)
+    }
+}
+
+/// Fixed surface or level
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FixedSurface {
+    /// Type of fixed surface (see Code Table 4.5)
+    pub surface_type: u8,
+    /// Scale factor of fixed surface
+    pub scale_factor: i8,
+    /// Scaled value of fixed surface
+    pub scaled_value: u32,
+}
+
+impl FixedSurface {
+    fn from_bytes(buf: &[u8]) -> Self {
+        Self {
+            surface_type: buf[0],
+            scale_factor: buf[1].into_grib_int(),
+            scaled_value: read_as!(u32, buf, 2),
+        }
+    }
+
+    /// Returns `true` if the type of the surface is missing.
+    pub fn is_missing(&self) -> bool {
+        self.surface_type == 255
+    }
+
+    /// Returns the value of the surface in the unit defined in Code
+    /// Table 4.5, or `None` if the scaled value is missing.  A missing
+    /// scale factor with a valid scaled value is regarded as 0.
+    pub fn value(&self) -> Option<f64> {
+        if self.scaled_value == u32::MAX {
+            return None;
+        }
+
+        let scale_factor = if self.scale_factor == i8::MIN + 1 {
+            0
+        } else {
+            self.scale_factor
+        };
+        ScaledValue {
+            scale_factor,
+            scaled_value: self.scaled_value.into_grib_int(),
+        }
+        .value()
+    }
+
+    /// Converts the surface into a typed level, or returns `None` if
+    /// the type of the surface is missing.
+    pub fn level(&self) -> Option<Level> {
+        if self.is_missing() {
+            return None;
+        }
+
+        let value = match lookup_surface(self.surface_type) {
+            Some((_, "")) => None,
+            _ => self.value(),
+        };
+        Some(Level {
+            surface_type: self.surface_type,
+            value,
+        })
+    }
+}
+
+/// Vertical position of data, which is either a single level or a layer
+/// between two levels
+#[derive(Debug, Clone, PartialEq)]
+pub enum LevelOrLayer {
+    Level(Level),
+    Layer(Layer),
+}
+
+impl Display for LevelOrLayer {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Level(level) => write!(f, "{}", level),
+            Self::Layer(layer) => write!(f, "{}", layer),
+        }
+    }
+}
+
+/// Level defined with a fixed surface type in Code Table 4.5
+#[derive(Debug, Clone, PartialEq)]
+pub struct Level {
+    /// Type of fixed surface (see Code Table 4.5)
+    pub surface_type: u8,
+    /// Value in the unit defined in Code Table 4.5, which is `None` if
+    /// the surface has no value or the value is missing
+    pub value: Option<f64>,
+}
+
+impl Level {
+    /// Returns the name of the surface type, or `None` if it is not
+    /// found in Code Table 4.5.
+    pub fn name(&self) -> Option<&'static str> {
+        lookup_surface(self.surface_type).map(|(name, _)| name)
+    }
+
+    /// Returns the unit of the value defined in Code Table 4.5, or
+    /// `None` if the surface type is unknown or has no value.
+    pub fn unit(&self) -> Option<&'static str> {
+        match lookup_surface(self.surface_type)? {
+            (_, "") => None,
+            (_, unit) => Some(unit),
+        }
+    }
+
+    /// Returns the value and the unit for display, where pressures are
+    /// converted from Pa to hPa.
+    pub fn display_value(&self) -> Option<(f64, &'static str)> {
+        let value = self.value?;
+        match self.unit() {
+            Some("Pa") => Some((value / 100., "hPa")),
+            Some(unit) => Some((value, unit)),
+            None => Some((value, "")),
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{}", name)?,
+            None => write!(f, "surface type {}", self.surface_type)?,
+        }
+        match self.display_value() {
+            Some((value, "")) => write!(f, " {}", value),
+            Some((value, unit)) => write!(f, " {} {}", value, unit),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Layer between two levels
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer {
+    /// Level defined with the first fixed surface
+    pub first: Level,
+    /// Level defined with the second fixed surface
+    pub second: Level,
+}
+
+impl Display for Layer {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let values = (self.first.display_value(), self.second.display_value());
+        match (self.first.name(), values) {
+            (Some(name), (Some((v1, unit)), Some((v2, _))))
+                if self.first.surface_type == self.second.surface_type =>
+            {
+                write!(f, "{} {}-{}", name, v1, v2)?;
+                if !unit.is_empty() {
+                    write!(f, " {}", unit)?;
+                }
+                Ok(())
+            }
+            _ => write!(f, "{} - {}", self.first, self.second),
+        }
+    }
+}
+
+/// Statistical processing information contained in templates such as
+/// Product Definition Template 4.8 "Average, accumulation, extreme
+/// values or other statistically processed values at a horizontal
+/// level or in a horizontal layer in a continuous or non-continuous
+/// time interval"
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StatisticalProcessing {
+    /// End of overall time interval
+    pub end_time: DateTime<Utc>,
+    /// Total number of data values missing in statistical process
+    pub num_missing: u32,
+    /// Specifications of time ranges, starting from the outermost
+    /// (or only) one
+    pub time_ranges: Vec<TimeRangeSpec>,
+}
+
+impl StatisticalProcessing {
+    const TIME_RANGE_START: usize = 12;
+
+    /// Parses octets from "year of end of overall time interval".
+    pub(crate) fn from_bytes(buf: &[u8]) -> Option<Self> {
+        if buf.len() < Self::TIME_RANGE_START {
+            return None;
+        }
+
+        let end_time = Utc
+            .with_ymd_and_hms(
+                read_as!(u16, buf, 0).into(),
+                buf[2].into(),
+                buf[3].into(),
+                buf[4].into(),
+                buf[5].into(),
+                buf[6].into(),
+            )
+            .single()?;
+        let num_ranges = usize::from(buf[7]);
+        let num_missing = read_as!(u32, buf, 8);
+
+        let end = Self::TIME_RANGE_START + num_ranges * TimeRangeSpec::SIZE;
+        let time_ranges = buf
+            .get(Self::TIME_RANGE_START..end)?
+            .chunks(TimeRangeSpec::SIZE)
+            .map(TimeRangeSpec::from_bytes)
+            .collect();
+
+        Some(Self {
+            end_time,
+            num_missing,
+            time_ranges,
+        })
+    }
+
+    /// Returns the overall time interval and the statistical process
+    /// of the outermost time range.
+    pub fn interval(&self) -> Option<StatisticalInterval> {
+        let outermost = self.time_ranges.first()?;
+        let length = i64::from(outermost.length);
+        let start = shift_time(self.end_time, outermost.length_unit, -length)?;
+
+        Some(StatisticalInterval {
+            start,
+            end: self.end_time,
+            statistic: outermost.statistical_process.into(),
+        })
+    }
+}
+
+/// Specification of a time range used in statistical processing
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TimeRangeSpec {
+    /// Statistical process used (see Code Table 4.10)
+    pub statistical_process: u8,
+    /// Type of time increment between successive fields used in the
+    /// statistical processing (see Code Table 4.11)
+    pub increment_type: u8,
+    /// Indicator of unit of time for time range (see Code Table 4.4)
+    pub length_unit: u8,
+    /// Length of the time range
+    pub length: u32,
+    /// Indicator of unit of time for the increment (see Code Table 4.4)
+    pub increment_unit: u8,
+    /// Time increment between successive fields
+    pub increment: u32,
+}
+
+impl TimeRangeSpec {
+    const SIZE: usize = 12;
+
+    fn from_bytes(buf: &[u8]) -> Self {
+        Self {
+            statistical_process: buf[0],
+            increment_type: buf[1],
+            length_unit: buf[2],
+            length: read_as!(u32, buf, 3),
+            increment_unit: buf[7],
+            increment: read_as!(u32, buf, 8),
+        }
+    }
+}
+
+/// Time interval over which values are statistically processed
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StatisticalInterval {
+    /// Start of the interval
+    pub start: DateTime<Utc>,
+    /// End of the interval
+    pub end: DateTime<Utc>,
+    /// Statistic computed over the interval
+    pub statistic: StatisticalProcess,
+}
+
+/// Statistical process defined in Code Table 4.10
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatisticalProcess {
+    Average,
+    Accumulation,
+    Maximum,
+    Minimum,
+    /// Difference (value at the end of the time range minus value at
+    /// the beginning)
+    Difference,
+    RootMeanSquare,
+    StandardDeviation,
+    /// Covariance (temporal variance)
+    Covariance,
+    /// Difference (value at the beginning of the time range minus
+    /// value at the end)
+    InverseDifference,
+    Ratio,
+    StandardizedAnomaly,
+    Summation,
+    Other(u8),
+}
+
+impl From<u8> for StatisticalProcess {
+    fn from(code: u8) -> Self {
+        match code {
+            0 => Self::Average,
+            1 => Self::Accumulation,
+            2 => Self::Maximum,
+            3 => Self::Minimum,
+            4 => Self::Difference,
+            5 => Self::RootMeanSquare,
+            6 => Self::StandardDeviation,
+            7 => Self::Covariance,
+            8 => Self::InverseDifference,
+            9 => Self::Ratio,
+            10 => Self::StandardizedAnomaly,
+            11 => Self::Summation,
+            n => Self::Other(n),
+        }
+    }
+}
+
+impl Display for StatisticalProcess {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Average => write!(f, "average"),
+            Self::Accumulation => write!(f, "accumulation"),
+            Self::Maximum => write!(f, "maximum"),
+            Self::Minimum => write!(f, "minimum"),
+            Self::Difference => write!(f, "difference"),
+            Self::RootMeanSquare => write!(f, "root mean square"),
+            Self::StandardDeviation => write!(f, "standard deviation"),
+            Self::Covariance => write!(f, "covariance"),
+            Self::InverseDifference => write!(f, "inverse difference"),
+            Self::Ratio => write!(f, "ratio"),
+            Self::StandardizedAnomaly => write!(f, "standardized anomaly"),
+            Self::Summation => write!(f, "summation"),
+            Self::Other(n) => write!(f, "statistical process {}", n),
+        }
+    }
+}
+
+/// Ensemble member information contained in Product Definition
+/// Templates 4.1 and 4.11
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EnsembleMember {
+    /// Type of ensemble forecast
+    pub ensemble_type: EnsembleType,
+    /// Perturbation number
+    pub perturbation_number: u8,
+    /// Number of forecasts in ensemble
+    pub num_members: u8,
+}
+
+impl EnsembleMember {
+    /// Parses octets from "type of ensemble forecast".
+    pub(crate) fn from_bytes(buf: &[u8]) -> Option<Self> {
+        let buf = buf.get(..3)?;
+        Some(Self {
+            ensemble_type: buf[0].into(),
+            perturbation_number: buf[1],
+            num_members: buf[2],
+        })
+    }
+}
+
+/// Type of ensemble forecast defined in Code Table 4.6
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnsembleType {
+    UnperturbedHighResolutionControl,
+    UnperturbedLowResolutionControl,
+    NegativelyPerturbed,
+    PositivelyPerturbed,
+    MultiModel,
+    Other(u8),
+}
+
+impl From<u8> for EnsembleType {
+    fn from(code: u8) -> Self {
+        match code {
+            0 => Self::UnperturbedHighResolutionControl,
+            1 => Self::UnperturbedLowResolutionControl,
+            2 => Self::NegativelyPerturbed,
+            3 => Self::PositivelyPerturbed,
+            4 => Self::MultiModel,
+            n => Self::Other(n),
+        }
+    }
+}
+
+impl Display for EnsembleType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::UnperturbedHighResolutionControl => {
+                write!(f, "unperturbed high-resolution control forecast")
+            }
+            Self::UnperturbedLowResolutionControl => {
+                write!(f, "unperturbed low-resolution control forecast")
+            }
+            Self::NegativelyPerturbed => write!(f, "negatively perturbed forecast"),
+            Self::PositivelyPerturbed => write!(f, "positively perturbed forecast"),
+            Self::MultiModel => write!(f, "multi-model forecast"),
+            Self::Other(n) => write!(f, "ensemble type {}", n),
+        }
+    }
+}
+
+/// Derived forecast information contained in Product Definition
+/// Templates 4.2 and 4.12
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DerivedForecast {
+    /// Derived forecast
+    pub derived_type: DerivedForecastType,
+    /// Number of forecasts in ensemble
+    pub num_members: u8,
+}
+
+impl DerivedForecast {
+    /// Parses octets from "derived forecast".
+    pub(crate) fn from_bytes(buf: &[u8]) -> Option<Self> {
+        let buf = buf.get(..2)?;
+        Some(Self {
+            derived_type: buf[0].into(),
+            num_members: buf[1],
+        })
+    }
+}
+
+/// Derived forecast defined in Code Table 4.7
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DerivedForecastType {
+    UnweightedMean,
+    WeightedMean,
+    StandardDeviationFromClusterMean,
+    NormalizedStandardDeviationFromClusterMean,
+    Spread,
+    LargeAnomalyIndex,
+    UnweightedMeanOfCluster,
+    InterquartileRange,
+    Minimum,
+    Maximum,
+    Other(u8),
+}
+
+impl From<u8> for DerivedForecastType {
+    fn from(code: u8) -> Self {
+        match code {
+            0 => Self::UnweightedMean,
+            1 => Self::WeightedMean,
+            2 => Self::StandardDeviationFromClusterMean,
+            3 => Self::NormalizedStandardDeviationFromClusterMean,
+            4 => Self::Spread,
+            5 => Self::LargeAnomalyIndex,
+            6 => Self::UnweightedMeanOfCluster,
+            7 => Self::InterquartileRange,
+            8 => Self::Minimum,
+            9 => Self::Maximum,
+            n => Self::Other(n),
+        }
+    }
+}
+
+impl Display for DerivedForecastType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::UnweightedMean => write!(f, "unweighted mean of all members"),
+            Self::WeightedMean => write!(f, "weighted mean of all members"),
+            Self::StandardDeviationFromClusterMean => {
+                write!(f, "standard deviation with respect to cluster mean")
+            }
+            Self::NormalizedStandardDeviationFromClusterMean => write!(
+                f,
+                "standard deviation with respect to cluster mean, normalized"
+            ),
+            Self::Spread => write!(f, "spread of all members"),
+            Self::LargeAnomalyIndex => write!(f, "large anomaly index of all members"),
+            Self::UnweightedMeanOfCluster => write!(f, "unweighted mean of the cluster members"),
+            Self::InterquartileRange => write!(f, "interquartile range"),
+            Self::Minimum => write!(f, "minimum of all ensemble members"),
+            Self::Maximum => write!(f, "maximum of all ensemble members"),
+            Self::Other(n) => write!(f, "derived forecast {}", n),
+        }
+    }
+}
+
+/// Probability information contained in Product Definition Templates
+/// 4.5 and 4.9
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProbabilityForecast {
+    /// Forecast probability number
+    pub probability_number: u8,
+    /// Total number of forecast probabilities
+    pub num_probabilities: u8,
+    /// Probability type (see Code Table 4.9)
+    pub probability_type: u8,
+    /// Lower limit
+    pub lower_limit: ScaledValue,
+    /// Upper limit
+    pub upper_limit: ScaledValue,
+}
+
+impl ProbabilityForecast {
+    /// Parses octets from "forecast probability number".
+    pub(crate) fn from_bytes(buf: &[u8]) -> Option<Self> {
+        let buf = buf.get(..13)?;
+        Some(Self {
+            probability_number: buf[0],
+            num_probabilities: buf[1],
+            probability_type: buf[2],
+            lower_limit: ScaledValue::from_bytes(&buf[3..8]),
+            upper_limit: ScaledValue::from_bytes(&buf[8..13]),
+        })
+    }
+
+    /// Returns the threshold of the event whose probability is given,
+    /// or `None` if the probability type is unknown or the limit used
+    /// is missing.
+    pub fn threshold(&self) -> Option<ProbabilityThreshold> {
+        let lower = || self.lower_limit.value();
+        let upper = || self.upper_limit.value();
+        let threshold = match self.probability_type {
+            0 => ProbabilityThreshold::Below(lower()?),
+            1 => ProbabilityThreshold::Above(upper()?),
+            2 => ProbabilityThreshold::Between(lower()?, upper()?),
+            3 => ProbabilityThreshold::Above(lower()?),
+            4 => ProbabilityThreshold::Below(upper()?),
+            5 => ProbabilityThreshold::EqualTo(lower()?),
+            _ => return None,
+        };
+        Some(threshold)
+    }
+}
+
+/// Threshold of an event defined with Code Table 4.9
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProbabilityThreshold {
+    /// Probability of event below the limit
+    Below(f64),
+    /// Probability of event above the limit
+    Above(f64),
+    /// Probability of event between the lower limit (inclusive) and
+    /// the upper limit (exclusive)
+    Between(f64, f64),
+    /// Probability of event equal to the limit
+    EqualTo(f64),
+}
+
+impl Display for ProbabilityThreshold {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Below(v) => write!(f, "prob <{}", v),
+            Self::Above(v) => write!(f, "prob >{}", v),
+            Self::Between(l, u) => write!(f, "prob >={} <{}", l, u),
+            Self::EqualTo(v) => write!(f, "prob ={}", v),
+        }
+    }
+}
+
+/// Value represented with a scale factor and a scaled value, which is
+/// `scaled_value * 10^(-scale_factor)`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ScaledValue {
+    /// Scale factor
+    pub scale_factor: i8,
+    /// Scaled value
+    pub scaled_value: i32,
+}
+
+impl ScaledValue {
+    fn from_bytes(buf: &[u8]) -> Self {
+        Self {
+            scale_factor: buf[0].into_grib_int(),
+            scaled_value: read_as!(u32, buf, 1).into_grib_int(),
+        }
+    }
+
+    /// Returns `true` if both the scale factor and the scaled value
+    /// are set to missing.
+    pub fn is_missing(&self) -> bool {
+        self.scale_factor == i8::MIN + 1 && self.scaled_value == i32::MIN + 1
+    }
+
+    /// Returns the value, or `None` if it is missing.
+    pub fn value(&self) -> Option<f64> {
+        if self.is_missing() {
+            return None;
+        }
+
+        let value = f64::from(self.scaled_value);
+        let factor = 10_f64.powi(self.scale_factor.abs().into());
+        if self.scale_factor >= 0 {
+            Some(value / factor)
+        } else {
+            Some(value * factor)
+        }
+    }
+}
+
+/// Atmospheric chemical constituent contained in Product Definition
+/// Templates 4.40 to 4.43
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChemicalConstituent {
+    /// Atmospheric chemical constituent type (see Code Table 4.230)
+    pub constituent_type: u16,
+}
+
+impl ChemicalConstituent {
+    /// Parses octets from "atmospheric chemical constituent type".
+    pub(crate) fn from_bytes(buf: &[u8]) -> Option<Self> {
+        let buf = buf.get(..2)?;
+        Some(Self {
+            constituent_type: read_as!(u16, buf, 0),
+        })
+    }
+
+    /// Returns the name of the constituent, or `None` if it is not
+    /// found in Code Table 4.230.
+    pub fn name(&self) -> Option<&'static str> {
+        lookup_constituent(self.constituent_type)
+    }
+}
+
+/// Aerosol information contained in Product Definition Templates 4.44
+/// to 4.48
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Aerosol {
+    /// Aerosol type (see Code Table 4.233)
+    pub aerosol_type: u16,
+    /// Interval of the particle size in metres
+    pub size: AerosolInterval,
+    /// Interval of the wavelength in metres, which is available only
+    /// in Template 4.48
+    pub wavelength: Option<AerosolInterval>,
+}
+
+impl Aerosol {
+    /// Parses octets from "aerosol type".  `with_wavelength` should be
+    /// `true` for templates with wavelength intervals.
+    pub(crate) fn from_bytes(buf: &[u8], with_wavelength: bool) -> Option<Self> {
+        let wavelength = if with_wavelength {
+            Some(AerosolInterval::from_bytes(buf.get(13..24)?))
+        } else {
+            None
+        };
+        let buf = buf.get(..13)?;
+        Some(Self {
+            aerosol_type: read_as!(u16, buf, 0),
+            size: AerosolInterval::from_bytes(&buf[2..13]),
+            wavelength,
+        })
+    }
+
+    /// Returns the name of the aerosol type, or `None` if it is not
+    /// found in the code table.
+    pub fn name(&self) -> Option<&'static str> {
+        lookup_constituent(self.aerosol_type)
+    }
+}
+
+/// Interval of the particle size or the wavelength of aerosols
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AerosolInterval {
+    /// Type of interval (see Code Table 4.91)
+    pub interval_type: u8,
+    /// First limit
+    pub first_limit: ScaledValue,
+    /// Second limit
+    pub second_limit: ScaledValue,
+}
+
+impl AerosolInterval {
+    fn from_bytes(buf: &[u8]) -> Self {
+        Self {
+            interval_type: buf[0],
+            first_limit: ScaledValue::from_bytes(&buf[1..6]),
+            second_limit: ScaledValue::from_bytes(&buf[6..11]),
+        }
+    }
+}
+
+/// Contents of Product Definition Templates 4.31 "Satellite product"
+/// and 4.32 "Analysis or forecast at a horizontal level or in a
+/// horizontal layer at a point in time for simulated (synthetic)
+/// satellite data"
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SatelliteProduct {
+    /// Parameter category (see Code Table 4.1)
+    pub parameter_category: u8,
+    /// Parameter number (see Code Table 4.2)
+    pub parameter_number: u8,
+    /// Type of generating process (see Code Table 4.3)
+    pub generating_process: u8,
+    /// Forecast time, which is available only in Template 4.32
+    pub forecast_time: Option<ForecastTime>,
+    /// Contributing spectral bands
+    pub bands: Vec<SatelliteBand>,
+}
+
+impl SatelliteProduct {
+    /// Parses octets from octet 10 of Section 4 of Template 4.31.
+    pub(crate) fn from_bytes_4_31(buf: &[u8]) -> Option<Self> {
+        let header = buf.get(..5)?;
+        Some(Self {
+            parameter_category: header[0],
+            parameter_number: header[1],
+            generating_process: header[2],
+            forecast_time: None,
+            bands: SatelliteBand::from_bytes_repeated(&buf[5..], header[4])?,
+        })
+    }
+
+    /// Parses octets from octet 10 of Section 4 of Template 4.32.
+    pub(crate) fn from_bytes_4_32(buf: &[u8]) -> Option<Self> {
+        let header = buf.get(..14)?;
+        Some(Self {
+            parameter_category: header[0],
+            parameter_number: header[1],
+            generating_process: header[2],
+            forecast_time: Some(ForecastTime {
+                unit: header[8],
+                value: read_as!(u32, header, 9),
+            }),
+            bands: SatelliteBand::from_bytes_repeated(&buf[14..], header[13])?,
+        })
+    }
+}
+
+/// Spectral band contributing to a satellite product
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SatelliteBand {
+    /// Satellite series (see Code Table 4.121)
+    pub satellite_series: u16,
+    /// Satellite number (see Code Table 4.122)
+    pub satellite_number: u16,
+    /// Instrument type (see Code Table 4.123)
+    pub instrument_type: u16,
+    /// Central wave number in m-1
+    pub central_wave_number: ScaledValue,
+}
+
+impl SatelliteBand {
+    const SIZE: usize = 11;
+
+    fn from_bytes_repeated(buf: &[u8], num_bands: u8) -> Option<Vec<Self>> {
+        let buf = buf.get(..Self::SIZE * usize::from(num_bands))?;
+        let bands = buf
+            .chunks_exact(Self::SIZE)
+            .map(|buf| Self {
+                satellite_series: read_as!(u16, buf, 0),
+                satellite_number: read_as!(u16, buf, 2),
+                instrument_type: read_as!(u16, buf, 4),
+                central_wave_number: ScaledValue::from_bytes(&buf[6..11]),
+            })
+            .collect();
+        Some(bands)
+    }
+}
+
+impl Display for SatelliteBand {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "satellite series {}, satellite number {}, instrument type {}, central wave number ",
+            self.satellite_series, self.satellite_number, self.instrument_type
+        )?;
+        match self.central_wave_number.value() {
+            Some(v) => write!(f, "{} m-1", v),
+            None => write!(f, "missing"),
+        }
+    }
+}
+
+/// Coefficients A and B of hybrid sigma-pressure levels, where the
+/// pressure at the half level `k` is `a[k] + b[k] * surface_pressure`
+#[derive(Debug, Clone, PartialEq)]
+pub struct HybridCoefficients {
+    /// Coefficients A in Pa, from the top to the bottom half level
+    pub a: Box<[f32]>,
+    /// Coefficients B, from the top to the bottom half level
+    pub b: Box<[f32]>,
+}
+
+impl HybridCoefficients {
+    /// Splits a list of vertical coordinate values which contains all
+    /// coefficients A followed by all coefficients B.  Returns `None`
+    /// if the number of values is odd or zero.
+    pub fn from_coordinates(coordinates: &[f32]) -> Option<Self> {
+        if coordinates.is_empty() || !coordinates.len().is_multiple_of(2) {
+            return None;
+        }
+
+        let (a, b) = coordinates.split_at(coordinates.len() / 2);
+        Some(Self {
+            a: a.into(),
+            b: b.into(),
+        })
+    }
+
+    /// Returns the number of full levels, which is one less than the
+    /// number of half levels.
+    pub fn num_levels(&self) -> usize {
+        self.a.len().saturating_sub(1)
+    }
+
+    /// Returns the pressure in Pa at the full level `level`, which is
+    /// numbered from 1 at the top as in the fixed surface value of
+    /// hybrid levels, computed as the mean of the pressures at the
+    /// half levels above and below.  Returns `None` if the level is
+    /// out of range.
+    pub fn full_level_pressure(&self, level: usize, surface_pressure: f32) -> Option<f32> {
+        if level == 0 || level > self.num_levels() {
+            return None;
+        }
+
+        let half = |k: usize| self.a[k] + self.b[k] * surface_pressure;
+        Some((half(level - 1) + half(level)) / 2.)
+    }
+
+    /// Computes pressures in Pa at the full level `level` for each grid
+    /// point from a field of surface pressure in Pa.  Missing values
+    /// are propagated as NaN.  Returns `None` if the level is out of
+    /// range.
+    pub fn full_level_pressures(
+        &self,
+        level: usize,
+        surface_pressure: &[f32],
+    ) -> Option<Box<[f32]>> {
+        surface_pressure
+            .iter()
+            .map(|ps| self.full_level_pressure(level, *ps))
+            .collect()
+    }
+}
+
+/// Lead time converted from a value in units of time defined in Code
+/// Table 4.4
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LeadTime {
+    /// Lead time of fixed length
+    Duration(Duration),
+    /// Lead time in calendar months, whose length depends on the time
+    /// it is added to
+    Months(i64),
+}
+
+impl LeadTime {
+    /// Converts `value` in units of time `unit` defined in Code Table
+    /// 4.4.  Returns `None` if the unit is unknown or the value is out
+    /// of range.
+    pub fn new(unit: u8, value: i64) -> Option<Self> {
+        let months = |n: i64| Some(Self::Months(n.checked_mul(value)?));
+        let duration = |seconds: i64| {
+            let seconds = seconds.checked_mul(value)?;
+            Some(Self::Duration(Duration::try_seconds(seconds)?))
+        };
+
+        match unit {
+            0 => duration(60),
+            1 => duration(3600),
+            2 => duration(86400),
+            3 => months(1),
+            4 => months(12),
+            5 => months(120),
+            6 => months(360),
+            7 => months(1200),
+            10 => duration(3 * 3600),
+            11 => duration(6 * 3600),
+            12 => duration(12 * 3600),
+            13 => duration(1),
+            _ => None,
+        }
+    }
+
+    /// Returns `time` shifted by the lead time.
+    pub fn add_to(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Duration(duration) => time.checked_add_signed(*duration),
+            Self::Months(n) => {
+                let abs = Months::new(n.unsigned_abs().try_into().ok()?);
+                if *n < 0 {
+                    time.checked_sub_months(abs)
+                } else {
+                    time.checked_add_months(abs)
+                }
+            }
+        }
+    }
+}
+
+/// Shifts `time` by `value` in units of time defined in Code Table
+/// 4.4.
+pub(crate) fn shift_time(time: DateTime<Utc>, unit: u8, value: i64) -> Option<DateTime<Utc>> {
+    LeadTime::new(unit, value)?.add_to(time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn analysis_or_forecast_from_bytes() {
+        let buf = vec![
+            0x0d, 0xc0, 0x02, 0xfa, 0xff, 0x00, 0x02, 0x1e, 0x01, 0x00, 0x00, 0x00, 0x03, 0x64,
+            0x02, 0x00, 0x00, 0xc3, 0x50, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        ];
+
+        assert_eq!(
+            AnalysisOrForecast::from_bytes(&buf),
+            Some(AnalysisOrForecast {
+                parameter_category: 13,
+                parameter_number: 192,
+                generating_process: 2,
+                background_process_id: 250,
+                forecast_process_id: 255,
+                cutoff_hours: 2,
+                cutoff_minutes: 30,
+                forecast_time: ForecastTime { unit: 1, value: 3 },
+                first_surface: FixedSurface {
+                    surface_type: 100,
+                    scale_factor: 2,
+                    scaled_value: 50000,
+                },
+                second_surface: FixedSurface {
+                    surface_type: 255,
+                    scale_factor: -127,
+                    scaled_value: 0xffffffff,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn analysis_or_forecast_from_short_bytes() {
+        assert_eq!(AnalysisOrForecast::from_bytes(&[0; 24]), None);
+    }
+
+    #[test]
+    fn statistical_processing_from_bytes() {
+        let buf = vec![
+            0x07, 0xe1, 0x02, 0x16, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02,
+            0x01, 0x00, 0x00, 0x00, 0x06, 0xff, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let actual = StatisticalProcessing::from_bytes(&buf).unwrap();
+        assert_eq!(
+            actual,
+            StatisticalProcessing {
+                end_time: Utc.with_ymd_and_hms(2017, 2, 22, 0, 0, 0).unwrap(),
+                num_missing: 0,
+                time_ranges: vec![TimeRangeSpec {
+                    statistical_process: 1,
+                    increment_type: 2,
+                    length_unit: 1,
+                    length: 6,
+                    increment_unit: 255,
+                    increment: 0,
+                }],
+            }
+        );
+        assert_eq!(
+            actual.interval(),
+            Some(StatisticalInterval {
+                start: Utc.with_ymd_and_hms(2017, 2, 21, 18, 0, 0).unwrap(),
+                end: Utc.with_ymd_and_hms(2017, 2, 22, 0, 0, 0).unwrap(),
+                statistic: StatisticalProcess::Accumulation,
+            })
+        );
+    }
+
+    #[test]
+    fn statistical_processing_with_multiple_time_ranges() {
+        let buf = vec![
+            0x07, 0xe1, 0x03, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x03, 0x00, 0x01,
+            0x03, 0x00, 0x00, 0x00, 0x01, 0x02, 0x00, 0x00, 0x00, 0x01, 0x02, 0x01, 0x01, 0x00,
+            0x00, 0x00, 0x18, 0xff, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let actual = StatisticalProcessing::from_bytes(&buf).unwrap();
+        assert_eq!(actual.num_missing, 3);
+        assert_eq!(actual.time_ranges.len(), 2);
+        assert_eq!(
+            actual.interval(),
+            Some(StatisticalInterval {
+                start: Utc.with_ymd_and_hms(2017, 2, 1, 0, 0, 0).unwrap(),
+                end: Utc.with_ymd_and_hms(2017, 3, 1, 0, 0, 0).unwrap(),
+                statistic: StatisticalProcess::Average,
+            })
+        );
+    }
+
+    #[test]
+    fn statistical_processing_from_truncated_bytes() {
+        let buf = vec![
+            0x07, 0xe1, 0x02, 0x16, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02,
+            0x01, 0x00, 0x00, 0x00, 0x06, 0xff, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        assert_eq!(StatisticalProcessing::from_bytes(&buf), None);
+    }
+
+    #[test]
+    fn ensemble_member_from_bytes() {
+        assert_eq!(
+            EnsembleMember::from_bytes(&[0x03, 0x0c, 0x33]),
+            Some(EnsembleMember {
+                ensemble_type: EnsembleType::PositivelyPerturbed,
+                perturbation_number: 12,
+                num_members: 51,
+            })
+        );
+        assert_eq!(EnsembleMember::from_bytes(&[0x03, 0x0c]), None);
+    }
+
+    #[test]
+    fn local_ensemble_type_as_other() {
+        assert_eq!(EnsembleType::from(192), EnsembleType::Other(192));
+    }
+
+    #[test]
+    fn derived_forecast_from_bytes() {
+        assert_eq!(
+            DerivedForecast::from_bytes(&[0x04, 0x1f]),
+            Some(DerivedForecast {
+                derived_type: DerivedForecastType::Spread,
+                num_members: 31,
+            })
+        );
+    }
+
+    #[test]
+    fn probability_forecast_from_bytes() {
+        let buf = vec![
+            0x01, 0x03, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x0a,
+        ];
+
+        let actual = ProbabilityForecast::from_bytes(&buf).unwrap();
+        assert_eq!(actual.probability_number, 1);
+        assert_eq!(actual.num_probabilities, 3);
+        assert!(actual.lower_limit.is_missing());
+        assert_eq!(actual.threshold(), Some(ProbabilityThreshold::Above(10.)));
+        assert_eq!(format!("{}", actual.threshold().unwrap()), "prob >10");
+    }
+
+    #[test]
+    fn probability_threshold_between_limits() {
+        let buf = vec![
+            0x00, 0x01, 0x02, 0x01, 0x00, 0x00, 0x00, 0x03, 0x81, 0x00, 0x00, 0x00, 0x02,
+        ];
+
+        let actual = ProbabilityForecast::from_bytes(&buf).unwrap();
+        assert_eq!(
+            actual.threshold(),
+            Some(ProbabilityThreshold::Between(0.3, 20.))
+        );
+        assert_eq!(format!("{}", actual.threshold().unwrap()), "prob >=0.3 <20");
+    }
+
+    #[test]
+    fn probability_threshold_with_missing_limit() {
+        let buf = vec![
+            0x00, 0x01, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x0a,
+        ];
+
+        assert_eq!(
+            ProbabilityForecast::from_bytes(&buf).unwrap().threshold(),
+            None
+        );
+    }
+
+    #[test]
+    fn hybrid_coefficients_full_level_pressure() {
+        let coefficients =
+            HybridCoefficients::from_coordinates(&[0., 5000., 0., 0., 0.5, 1.]).unwrap();
+
+        assert_eq!(coefficients.num_levels(), 2);
+        assert_eq!(coefficients.full_level_pressure(1, 100000.), Some(27500.));
+        assert_eq!(coefficients.full_level_pressure(2, 100000.), Some(77500.));
+        assert_eq!(coefficients.full_level_pressure(0, 100000.), None);
+        assert_eq!(coefficients.full_level_pressure(3, 100000.), None);
+    }
+
+    #[test]
+    fn hybrid_coefficients_full_level_pressures() {
+        let coefficients = HybridCoefficients::from_coordinates(&[0., 0., 0., 1.]).unwrap();
+        let actual = coefficients
+            .full_level_pressures(1, &[100000., 90000., f32::NAN])
+            .unwrap();
+
+        assert_eq!(&actual[..2], &[50000., 45000.]);
+        assert!(actual[2].is_nan());
+    }
+
+    #[test]
+    fn hybrid_coefficients_from_odd_number_of_coordinates() {
+        assert_eq!(HybridCoefficients::from_coordinates(&[0., 0., 1.]), None);
+        assert_eq!(HybridCoefficients::from_coordinates(&[]), None);
+    }
+
+    #[test]
+    fn lead_time_of_time_units() {
+        assert_eq!(
+            ForecastTime { unit: 10, value: 2 }.lead_time(),
+            Some(LeadTime::Duration(Duration::try_hours(6).unwrap()))
+        );
+        assert_eq!(
+            ForecastTime { unit: 4, value: 2 }.lead_time(),
+            Some(LeadTime::Months(24))
+        );
+        assert_eq!(ForecastTime { unit: 8, value: 2 }.lead_time(), None);
+    }
+
+    #[test]
+    fn valid_time_with_calendar_months() {
+        let ref_time = Utc.with_ymd_and_hms(2020, 1, 31, 0, 0, 0).unwrap();
+
+        assert_eq!(
+            ForecastTime { unit: 3, value: 1 }.valid_time(ref_time),
+            Some(Utc.with_ymd_and_hms(2020, 2, 29, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            ForecastTime { unit: 2, value: 1 }.valid_time(ref_time),
+            Some(Utc.with_ymd_and_hms(2020, 2, 1, 0, 0, 0).unwrap())
+        );
+    }
+
+    fn surface(surface_type: u8, scale_factor: i8, scaled_value: u32) -> FixedSurface {
+        FixedSurface {
+            surface_type,
+            scale_factor,
+            scaled_value,
+        }
+    }
+
+    #[test]
+    fn level_of_isobaric_surface_in_hpa() {
+        let level = surface(100, 0, 50000).level().unwrap();
+
+        assert_eq!(level.value, Some(50000.));
+        assert_eq!(level.unit(), Some("Pa"));
+        assert_eq!(level.display_value(), Some((500., "hPa")));
+        assert_eq!(level.to_string(), "Isobaric surface 500 hPa");
+    }
+
+    #[test]
+    fn level_without_value() {
+        let level = surface(1, -127, 0xffffffff).level().unwrap();
+
+        assert_eq!(level.value, None);
+        assert_eq!(level.unit(), None);
+        assert_eq!(level.to_string(), "Ground or water surface");
+        assert_eq!(surface(255, -127, 0xffffffff).level(), None);
+    }
+
+    #[test]
+    fn fixed_surface_value_edge_cases() {
+        assert_eq!(surface(103, -127, 10).value(), Some(10.));
+        assert_eq!(surface(103, -2, 5).value(), Some(500.));
+        assert_eq!(surface(106, 0, 0x80000005).value(), Some(-5.));
+        assert_eq!(surface(103, 0, 0xffffffff).value(), None);
+    }
+
+    #[test]
+    fn level_or_layer_of_analysis_or_forecast() {
+        let mut product = AnalysisOrForecast::from_bytes(&[0; 25]).unwrap();
+        product.first_surface = surface(103, 0, 2);
+        product.second_surface = surface(255, -127, 0xffffffff);
+
+        assert_eq!(
+            product.level_or_layer().map(|l| l.to_string()),
+            Some("Specified height level above ground 2 m".to_owned())
+        );
+
+        product.first_surface = surface(106, 0, 0);
+        product.second_surface = surface(106, 1, 1);
+        assert_eq!(
+            product.level_or_layer(),
+            Some(LevelOrLayer::Layer(Layer {
+                first: Level {
+                    surface_type: 106,
+                    value: Some(0.),
+                },
+                second: Level {
+                    surface_type: 106,
+                    value: Some(0.1),
+                },
+            }))
+        );
+        assert_eq!(
+            product.level_or_layer().map(|l| l.to_string()),
+            Some("Depth below land surface 0-0.1 m".to_owned())
+        );
+
+        product.first_surface = surface(1, -127, 0xffffffff);
+        product.second_surface = surface(100, 0, 50000);
+        assert_eq!(
+            product.level_or_layer().map(|l| l.to_string()),
+            Some("Ground or water surface - Isobaric surface 500 hPa".to_owned())
+        );
+
+        product.first_surface = surface(255, -127, 0xffffffff);
+        assert_eq!(product.level_or_layer(), None);
+    }
+}
@@ -5,33 +5,28 @@ use std::io::{self, Read, Seek, SeekFrom};
 use std::result::Result;
 
 use crate::context::{
-    BitMap, GridDefinition, Identification, ProdDefinition, ReprDefinition, SectionBody,
+    BitMap, GridDefinition, Identification, Indicator, ProdDefinition, ReprDefinition, SectionBody,
     SectionInfo,
 };
+use crate::utils::read_as;
 
-const SECT0_IS_MAGIC: &'static [u8] = b"GRIB";
+const SECT0_IS_MAGIC: &[u8] = b"GRIB";
 const SECT0_IS_MAGIC_SIZE: usize = SECT0_IS_MAGIC.len();
 const SECT0_IS_SIZE: usize = 16;
 const SECT_HEADER_SIZE: usize = 5;
-const SECT8_ES_MAGIC: &'static [u8] = b"7777";
+const SECT8_ES_MAGIC: &[u8] = b"7777";
 const SECT8_ES_SIZE: usize = SECT8_ES_MAGIC.len();
 
-macro_rules! read_as {
-    ($ty:ty, $buf:ident, $start:expr) => {{
-        let end = $start + std::mem::size_of::<$ty>();
-        <$ty>::from_be_bytes($buf[$start..end].try_into().unwrap())
-    }};
-}
-
 pub trait Grib2Read: Read + Seek {
     fn scan(&mut self) -> Result<Box<[SectionInfo]>, ParseError> {
-        let whole_size = self.read_sect0()?;
+        let indicator = self.read_sect0()?;
+        let whole_size = indicator.total_length as usize;
         let mut rest_size = whole_size - SECT0_IS_SIZE;
         let mut sects = vec![SectionInfo {
             num: 0,
             offset: 0,
             size: SECT0_IS_SIZE,
-            body: None,
+            body: Some(SectionBody::Section0(indicator)),
         }];
 
         loop {
@@ -57,11 +52,28 @@ pub trait Grib2Read: Read + Seek {
         Ok(sects.into_boxed_slice())
     }
 
-    fn read_sect0(&mut self) -> Result<usize, ParseError>;
+    fn read_sect0(&mut self) -> Result<Indicator, ParseError>;
     fn read_sect8(&mut self) -> Result<(), ParseError>;
     fn read_sect_meta(&mut self) -> Result<SectionInfo, ParseError>;
     fn read_sect(&mut self, meta: &SectionInfo) -> Result<SectionBody, ParseError>;
     fn read_sect_body_bytes(&mut self, meta: &SectionInfo) -> Result<Box<[u8]>, ParseError>;
+
+    /// Reads the body of a section into `buf`, replacing its contents, so
+    /// that the allocation of `buf` can be reused across reads.
+    ///
+    /// The default implementation allocates through
+    /// `read_sect_body_bytes`; implementations should override it to read
+    /// into `buf` directly.
+    fn read_sect_body_bytes_into(
+        &mut self,
+        meta: &SectionInfo,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), ParseError> {
+        let bytes = self.read_sect_body_bytes(meta)?;
+        buf.clear();
+        buf.extend_from_slice(&bytes);
+        Ok(())
+    }
 }
 
 pub struct SeekableGrib2Reader<R> {
@@ -91,7 +103,7 @@ impl<S: Seek> Seek for SeekableGrib2Reader<S> {
 }
 
 impl<R: Read + Seek> Grib2Read for SeekableGrib2Reader<R> {
-    fn read_sect0(&mut self) -> Result<usize, ParseError> {
+    fn read_sect0(&mut self) -> Result<Indicator, ParseError> {
         let mut buf = [0; SECT0_IS_SIZE];
         self.read_exact(&mut buf[..])
             .map_err(|e| ParseError::FileTypeCheckError(e.to_string()))?;
@@ -104,9 +116,10 @@ impl<R: Read + Seek> Grib2Read for SeekableGrib2Reader<R> {
             return Err(ParseError::GRIBVersionMismatch(version));
         }
 
-        let fsize = read_as!(u64, buf, 8);
-
-        Ok(fsize as usize)
+        Ok(Indicator {
+            discipline: buf[6],
+            total_length: read_as!(u64, buf, 8),
+        })
     }
 
     fn read_sect8(&mut self) -> Result<(), ParseError> {
@@ -156,14 +169,25 @@ impl<R: Read + Seek> Grib2Read for SeekableGrib2Reader<R> {
     }
 
     fn read_sect_body_bytes(&mut self, meta: &SectionInfo) -> Result<Box<[u8]>, ParseError> {
+        let mut buf = Vec::new();
+        self.read_sect_body_bytes_into(meta, &mut buf)?;
+        Ok(buf.into_boxed_slice())
+    }
+
+    fn read_sect_body_bytes_into(
+        &mut self,
+        meta: &SectionInfo,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), ParseError> {
         let body_offset = meta.offset + SECT_HEADER_SIZE;
         self.seek(SeekFrom::Start(body_offset as u64))?;
 
         let body_size = meta.size - SECT_HEADER_SIZE;
-        let mut buf = vec![0; body_size];
-        self.read_exact(&mut buf.as_mut_slice())?;
+        buf.clear();
+        buf.resize(body_size, 0);
+        self.read_exact(buf.as_mut_slice())?;
 
-        Ok(buf.into_boxed_slice())
+        Ok(())
     }
 }
 
@@ -184,8 +208,15 @@ pub fn unpack_sect1_body<R: Read>(f: &mut R, body_size: usize) -> Result<Section
         local_table_version: buf[5],
         ref_time_significance: buf[6],
         ref_time: Utc
-            .ymd(read_as!(u16, buf, 7).into(), buf[9].into(), buf[10].into())
-            .and_hms(buf[11].into(), buf[12].into(), buf[13].into()),
+            .with_ymd_and_hms(
+                read_as!(u16, buf, 7).into(),
+                buf[9].into(),
+                buf[10].into(),
+                buf[11].into(),
+                buf[12].into(),
+                buf[13].into(),
+            )
+            .unwrap(),
         prod_status: buf[14],
         data_type: buf[15],
     }))
@@ -206,14 +237,13 @@ pub fn unpack_sect3_body<R: Read>(f: &mut R, body_size: usize) -> Result<Section
     f.read_exact(&mut buf[..])?;
 
     let len_extra = body_size - buf.len();
-    if len_extra > 0 {
-        let mut buf = vec![0; len_extra];
-        f.read_exact(&mut buf[..])?;
-    }
+    let mut templated = vec![0; len_extra]; // octet 15-nn
+    f.read_exact(&mut templated[..])?;
 
     Ok(SectionBody::Section3(GridDefinition {
         num_points: read_as!(u32, buf, 1),
         grid_tmpl_num: read_as!(u16, buf, 7),
+        templated: templated.into_boxed_slice(),
     }))
 }
 
@@ -222,14 +252,13 @@ pub fn unpack_sect4_body<R: Read>(f: &mut R, body_size: usize) -> Result<Section
     f.read_exact(&mut buf[..])?;
 
     let len_extra = body_size - buf.len();
-    if len_extra > 0 {
-        let mut buf = vec![0; len_extra];
-        f.read_exact(&mut buf[..])?;
-    }
+    let mut templated = vec![0; len_extra]; // octet 10-nn
+    f.read_exact(&mut templated[..])?;
 
     Ok(SectionBody::Section4(ProdDefinition {
         num_coordinates: read_as!(u16, buf, 0),
         prod_tmpl_num: read_as!(u16, buf, 2),
+        templated: templated.into_boxed_slice(),
     }))
 }
 
@@ -326,7 +355,10 @@ mod tests {
                     num: 0,
                     offset: 0,
                     size: 16,
-                    body: None,
+                    body: Some(SectionBody::Section0(Indicator {
+                        discipline: 0,
+                        total_length: 10321,
+                    })),
                 },
                 SectionInfo {
                     num: 1,
@@ -338,7 +370,7 @@ mod tests {
                         master_table_version: 5,
                         local_table_version: 1,
                         ref_time_significance: 0,
-                        ref_time: Utc.ymd(2016, 8, 22).and_hms(2, 0, 0),
+                        ref_time: Utc.with_ymd_and_hms(2016, 8, 22, 2, 0, 0).unwrap(),
                         prod_status: 0,
                         data_type: 2,
                     })),
@@ -350,6 +382,14 @@ mod tests {
                     body: Some(SectionBody::Section3(GridDefinition {
                         num_points: 86016,
                         grid_tmpl_num: 0,
+                        templated: vec![
+                            0x04, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01, 0x03, 0xcd, 0x39, 0xfa, 0x01,
+                            0x03, 0xc9, 0xf6, 0xa3, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x50,
+                            0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0x02, 0xdb, 0xc9, 0x3d,
+                            0x07, 0x09, 0x7d, 0xa4, 0x30, 0x01, 0x31, 0xcf, 0xc3, 0x08, 0xef, 0xdd,
+                            0x5c, 0x00, 0x01, 0xe8, 0x48, 0x00, 0x01, 0x45, 0x85, 0x00,
+                        ]
+                        .into_boxed_slice(),
                     })),
                 },
                 SectionInfo {
@@ -359,6 +399,12 @@ mod tests {
                     body: Some(SectionBody::Section4(ProdDefinition {
                         num_coordinates: 0,
                         prod_tmpl_num: 0,
+                        templated: vec![
+                            0xc1, 0x00, 0x00, 0x99, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                            0x00, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                            0xff,
+                        ]
+                        .into_boxed_slice(),
                     })),
                 },
                 SectionInfo {
@@ -391,6 +437,12 @@ mod tests {
                     body: Some(SectionBody::Section4(ProdDefinition {
                         num_coordinates: 0,
                         prod_tmpl_num: 0,
+                        templated: vec![
+                            0xc1, 0x00, 0x02, 0x99, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                            0x0a, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                            0xff,
+                        ]
+                        .into_boxed_slice(),
                     })),
                 },
                 SectionInfo {
@@ -423,6 +475,12 @@ mod tests {
                     body: Some(SectionBody::Section4(ProdDefinition {
                         num_coordinates: 0,
                         prod_tmpl_num: 0,
+                        templated: vec![
+                            0xc1, 0x00, 0x02, 0x99, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                            0x14, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                            0xff,
+                        ]
+                        .into_boxed_slice(),
                     })),
                 },
                 SectionInfo {
@@ -455,6 +513,12 @@ mod tests {
                     body: Some(SectionBody::Section4(ProdDefinition {
                         num_coordinates: 0,
                         prod_tmpl_num: 0,
+                        templated: vec![
+                            0xc1, 0x00, 0x02, 0x99, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                            0x1e, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                            0xff,
+                        ]
+                        .into_boxed_slice(),
                     })),
                 },
                 SectionInfo {
@@ -487,6 +551,12 @@ mod tests {
                     body: Some(SectionBody::Section4(ProdDefinition {
                         num_coordinates: 0,
                         prod_tmpl_num: 0,
+                        templated: vec![
+                            0xc1, 0x00, 0x02, 0x99, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                            0x28, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                            0xff,
+                        ]
+                        .into_boxed_slice(),
                     })),
                 },
                 SectionInfo {
@@ -519,6 +589,12 @@ mod tests {
                     body: Some(SectionBody::Section4(ProdDefinition {
                         num_coordinates: 0,
                         prod_tmpl_num: 0,
+                        templated: vec![
+                            0xc1, 0x00, 0x02, 0x99, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                            0x32, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                            0xff,
+                        ]
+                        .into_boxed_slice(),
                     })),
                 },
                 SectionInfo {
@@ -551,6 +627,12 @@ mod tests {
                     body: Some(SectionBody::Section4(ProdDefinition {
                         num_coordinates: 0,
                         prod_tmpl_num: 0,
+                        templated: vec![
+                            0xc1, 0x00, 0x02, 0x99, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                            0x3c, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                            0xff,
+                        ]
+                        .into_boxed_slice(),
                     })),
                 },
                 SectionInfo {
@@ -0,0 +1,290 @@
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+
+use crate::context::SphericalHarmonics;
+use crate::decoder::SpectralPackingDecodeError;
+
+/// Latitude-longitude grid onto which spherical harmonic coefficients
+/// are evaluated.
+///
+/// Grid points are ordered from north to south and from west to east
+/// starting at longitude 0, with longitudes equally spaced.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SynthesisGrid {
+    /// Gaussian grid with `num_lat` latitudes in total
+    Gaussian { num_lat: usize, num_lon: usize },
+    /// Regular grid with `num_lat` latitudes including both poles
+    Regular { num_lat: usize, num_lon: usize },
+}
+
+impl SynthesisGrid {
+    /// Returns latitudes of the grid in degrees.
+    pub fn latitudes(&self) -> Box<[f64]> {
+        match self {
+            Self::Gaussian { num_lat, .. } => gaussian_latitudes(*num_lat),
+            Self::Regular { num_lat, .. } => regular_latitudes(*num_lat),
+        }
+    }
+
+    pub fn num_lon(&self) -> usize {
+        match self {
+            Self::Gaussian { num_lon, .. } => *num_lon,
+            Self::Regular { num_lon, .. } => *num_lon,
+        }
+    }
+}
+
+/// Maximum number of Newton iterations for a Gaussian latitude.  The
+/// iteration converges quadratically from the initial guess, so the limit
+/// is reached only if rounding errors keep it from converging.
+const MAX_NEWTON_ITERATIONS: usize = 100;
+
+/// Tolerance of the Newton iteration, which is a few units in the last
+/// place of the sine of latitude, whose magnitude is at most 1.
+const NEWTON_TOLERANCE: f64 = 4. * f64::EPSILON;
+
+/// Computes `num_lat` Gaussian latitudes in degrees from north to
+/// south, which are the roots of the Legendre polynomial of degree
+/// `num_lat`.
+pub fn gaussian_latitudes(num_lat: usize) -> Box<[f64]> {
+    let n = num_lat as f64;
+    let mut lats = vec![0.; num_lat];
+
+    for i in 0..num_lat.div_ceil(2) {
+        let mut x = (std::f64::consts::PI * (i as f64 + 0.75) / (n + 0.5)).cos();
+        for _ in 0..MAX_NEWTON_ITERATIONS {
+            let (p, dp) = legendre_polynomial(num_lat, x);
+            let dx = p / dp;
+            x -= dx;
+            if dx.abs() <= NEWTON_TOLERANCE {
+                break;
+            }
+        }
+        let lat = x.asin().to_degrees();
+        lats[i] = lat;
+        lats[num_lat - 1 - i] = -lat;
+    }
+
+    lats.into_boxed_slice()
+}
+
+/// Returns the value and the derivative of the Legendre polynomial of
+/// degree `n` at `x`.
+fn legendre_polynomial(n: usize, x: f64) -> (f64, f64) {
+    let mut p0 = 1.;
+    let mut p1 = x;
+    for k in 2..=n {
+        let k = k as f64;
+        let p2 = ((2. * k - 1.) * x * p1 - (k - 1.) * p0) / k;
+        p0 = p1;
+        p1 = p2;
+    }
+    let p = if n == 0 { p0 } else { p1 };
+    let dp = n as f64 * (x * p - p0) / (x * x - 1.);
+    (p, dp)
+}
+
+/// Computes `num_lat` equally spaced latitudes in degrees from the
+/// North Pole to the South Pole.
+pub fn regular_latitudes(num_lat: usize) -> Box<[f64]> {
+    if num_lat <= 1 {
+        return vec![0.; num_lat].into_boxed_slice();
+    }
+
+    let step = 180. / (num_lat - 1) as f64;
+    (0..num_lat)
+        .map(|i| 90. - step * i as f64)
+        .collect::<Vec<_>>()
+        .into_boxed_slice()
+}
+
+/// Evaluates spherical harmonic coefficients onto a latitude-longitude
+/// grid.
+///
+/// `coefficients` are pairs of real and imaginary parts ordered by
+/// zonal wavenumber `m` and then by total wavenumber `n`, as stored in
+/// GRIB2 data with Grid Definition Template 3.50.  The associated
+/// Legendre functions are normalized so that the (0,0) coefficient is
+/// the global mean of the field.  Zonal wavenumbers which cannot be
+/// represented with `num_lon` longitudes are ignored.
+pub fn synthesize(
+    coefficients: &[f32],
+    truncation: &SphericalHarmonics,
+    grid: &SynthesisGrid,
+) -> Result<Box<[f32]>, SpectralPackingDecodeError> {
+    if coefficients.len() != truncation.num_coefficients() * 2 {
+        return Err(SpectralPackingDecodeError::LengthMismatch);
+    }
+
+    let num_lon = grid.num_lon();
+    if num_lon == 0 {
+        return Ok(Vec::new().into_boxed_slice());
+    }
+    let max_m = std::cmp::min(truncation.m as usize, num_lon.saturating_sub(1) / 2);
+    let mut starts = Vec::with_capacity(truncation.m as usize + 1);
+    let mut pos = 0;
+    for m in 0..=truncation.m {
+        starts.push(pos);
+        pos += ((truncation.max_n(m) + 1).saturating_sub(m) as usize) * 2;
+    }
+
+    let fft = FftPlanner::new().plan_fft_inverse(num_lon);
+    let lats = grid.latitudes();
+    let mut out_buf = Vec::with_capacity(lats.len() * num_lon);
+    let mut spectrum = vec![Complex::new(0., 0.); num_lon];
+
+    for lat in lats.iter() {
+        let (sin_lat, cos_lat) = lat.to_radians().sin_cos();
+        spectrum.iter_mut().for_each(|c| *c = Complex::new(0., 0.));
+
+        // normalized P_m^m
+        let mut p_mm = 1.;
+        for m in 0..=max_m {
+            if m > 0 {
+                let m = m as f64;
+                p_mm *= ((2. * m + 1.) / (2. * m)).sqrt() * cos_lat;
+            }
+
+            let mut sum = Complex::new(0., 0.);
+            let mut pos = starts[m];
+            let mut p_prev = 0.;
+            let mut p_curr = p_mm;
+            for n in m..=truncation.max_n(m as u32) as usize {
+                if n > m {
+                    let (nf, mf) = (n as f64, m as f64);
+                    let a = ((4. * nf * nf - 1.) / (nf * nf - mf * mf)).sqrt();
+                    let b = (((nf - 1.) * (nf - 1.) - mf * mf) / (4. * (nf - 1.) * (nf - 1.) - 1.))
+                        .sqrt();
+                    let p_next = a * (sin_lat * p_curr - b * p_prev);
+                    p_prev = p_curr;
+                    p_curr = p_next;
+                }
+                let coef = Complex::new(
+                    f64::from(coefficients[pos]),
+                    f64::from(coefficients[pos + 1]),
+                );
+                sum += coef * p_curr;
+                pos += 2;
+            }
+
+            if m == 0 {
+                spectrum[0] = Complex::new(sum.re, 0.);
+            } else {
+                spectrum[m] = sum;
+                spectrum[num_lon - m] = sum.conj();
+            }
+        }
+
+        fft.process(&mut spectrum);
+        out_buf.extend(spectrum.iter().map(|c| c.re as f32));
+    }
+
+    Ok(out_buf.into_boxed_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangular(m: u32) -> SphericalHarmonics {
+        SphericalHarmonics {
+            j: m,
+            k: m,
+            m,
+            repr_type: 1,
+            repr_mode: 1,
+        }
+    }
+
+    #[test]
+    fn gaussian_latitudes_of_legendre_roots() {
+        let lats = gaussian_latitudes(4);
+        let expected: Vec<_> = [
+            0.861_136_311_594_052_6_f64,
+            0.339_981_043_584_856_3,
+            -0.339_981_043_584_856_3,
+            -0.861_136_311_594_052_6,
+        ]
+        .iter()
+        .map(|mu| mu.asin().to_degrees())
+        .collect();
+
+        for (actual, expected) in lats.iter().zip(expected.iter()) {
+            assert!((actual - expected).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn gaussian_latitudes_of_high_resolution_grid() {
+        // The Newton iteration ends for the 2560 latitudes of an O1280 grid.
+        let lats = gaussian_latitudes(2560);
+        assert!(lats.windows(2).all(|w| w[0] > w[1]));
+        assert!(lats[0] < 90.);
+        assert_eq!(lats[0], -lats[2559]);
+    }
+
+    #[test]
+    fn regular_latitudes_from_pole_to_pole() {
+        assert_eq!(
+            regular_latitudes(5),
+            vec![90., 45., 0., -45., -90.].into_boxed_slice()
+        );
+    }
+
+    #[test]
+    fn synthesize_global_mean() {
+        let truncation = triangular(2);
+        let mut coefficients = vec![0.; truncation.num_coefficients() * 2];
+        coefficients[0] = 11.5;
+        let grid = SynthesisGrid::Gaussian {
+            num_lat: 4,
+            num_lon: 8,
+        };
+
+        let actual = synthesize(&coefficients, &truncation, &grid).unwrap();
+
+        assert_eq!(actual.len(), 32);
+        for value in actual.iter() {
+            assert!((value - 11.5).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn synthesize_zonal_and_wave_components() {
+        let truncation = triangular(1);
+        // (m, n) = (0, 0), (0, 1), (1, 1)
+        let coefficients = vec![0., 0., 1., 0., 0.5, 0.25];
+        let grid = SynthesisGrid::Regular {
+            num_lat: 5,
+            num_lon: 4,
+        };
+
+        let actual = synthesize(&coefficients, &truncation, &grid).unwrap();
+
+        let lats = grid.latitudes();
+        for (i, lat) in lats.iter().enumerate() {
+            let (mu, cos_lat) = lat.to_radians().sin_cos();
+            for j in 0..4 {
+                let lon = std::f64::consts::PI * 2. * j as f64 / 4.;
+                let p10 = 3_f64.sqrt() * mu;
+                let p11 = 1.5_f64.sqrt() * cos_lat;
+                let expected = p10 + 2. * p11 * (0.5 * lon.cos() - 0.25 * lon.sin());
+                assert!((f64::from(actual[i * 4 + j]) - expected).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn synthesize_with_wrong_length() {
+        let truncation = triangular(1);
+        let grid = SynthesisGrid::Regular {
+            num_lat: 3,
+            num_lon: 4,
+        };
+
+        assert_eq!(
+            synthesize(&[0.; 4], &truncation, &grid),
+            Err(SpectralPackingDecodeError::LengthMismatch)
+        );
+    }
+}
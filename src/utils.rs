@@ -1,16 +1,24 @@
+macro_rules! read_as {
+    ($ty:ty, $buf:ident, $start:expr) => {{
+        let end = $start + std::mem::size_of::<$ty>();
+        <$ty>::from_be_bytes($buf[$start..end].try_into().unwrap())
+    }};
+}
+pub(crate) use read_as;
+
 pub(crate) trait GribInt<I> {
-    fn into_grib_int(&self) -> I;
+    fn into_grib_int(self) -> I;
 }
 
 macro_rules! add_impl_for_ints {
     ($(($ty_src:ty, $ty_dst:ty),)*) => ($(
         impl GribInt<$ty_dst> for $ty_src {
-            fn into_grib_int(&self) -> $ty_dst {
+            fn into_grib_int(self) -> $ty_dst {
                 if self.leading_zeros() == 0 {
                     let abs = (self << 1 >> 1) as $ty_dst;
                     -abs
                 } else {
-                    *self as $ty_dst
+                    self as $ty_dst
                 }
             }
         }
@@ -24,6 +32,46 @@ add_impl_for_ints! {
     (u64, i64),
 }
 
+/// Reader of unsigned integers of arbitrary bit widths packed one after
+/// another into a big-endian bit stream
+pub(crate) struct BitReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub(crate) fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// Reads `nbit` bits as an unsigned integer, or returns `None` if
+    /// there are not enough bits left.  `nbit` should be at most 32.
+    pub(crate) fn read(&mut self, nbit: usize) -> Option<u32> {
+        if nbit == 0 {
+            return Some(0);
+        }
+
+        let end = self.pos + nbit;
+        if end > self.buf.len() * 8 {
+            return None;
+        }
+
+        let first = self.pos / 8;
+        let last = (end - 1) / 8;
+        let acc = self.buf[first..=last]
+            .iter()
+            .fold(0_u64, |acc, byte| acc << 8 | u64::from(*byte));
+        let acc = acc >> ((last + 1) * 8 - end);
+        self.pos = end;
+        Some((acc & ((1 << nbit) - 1)) as u32)
+    }
+
+    /// Skips bits up to the next octet boundary.
+    pub(crate) fn align(&mut self) {
+        self.pos = self.pos.div_ceil(8) * 8;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,4 +114,39 @@ mod tests {
 
         assert_eq!(actual, output);
     }
+
+    #[test]
+    fn bit_reader_across_byte_boundaries() {
+        let input = vec![0b10110011, 0b01011100, 0b11110000];
+        let mut reader = BitReader::new(&input);
+
+        assert_eq!(reader.read(3), Some(0b101));
+        assert_eq!(reader.read(7), Some(0b1001101));
+        assert_eq!(reader.read(0), Some(0));
+        assert_eq!(reader.read(12), Some(0b011100111100));
+        assert_eq!(reader.read(3), None);
+        assert_eq!(reader.read(2), Some(0));
+    }
+
+    #[test]
+    fn bit_reader_of_32_bits_with_offset() {
+        let input = vec![0x7f, 0xff, 0xff, 0xff, 0x80];
+        let mut reader = BitReader::new(&input);
+
+        assert_eq!(reader.read(1), Some(0));
+        assert_eq!(reader.read(32), Some(u32::MAX));
+        assert_eq!(reader.read(7), Some(0));
+    }
+
+    #[test]
+    fn bit_reader_aligned_to_octet_boundary() {
+        let input = vec![0b11100000, 0b10100000];
+        let mut reader = BitReader::new(&input);
+
+        reader.align();
+        assert_eq!(reader.read(3), Some(0b111));
+        reader.align();
+        reader.align();
+        assert_eq!(reader.read(3), Some(0b101));
+    }
 }
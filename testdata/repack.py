@@ -0,0 +1,543 @@
+#!/usr/bin/env python3
+"""Repacks a field of a GRIB2 file packed with simple packing (Data
+Representation Template 5.0) using complex packing (Template 5.2),
+complex packing and spatial differencing (Template 5.3), JPEG 2000 code
+stream packing (Template 5.40), PNG packing (Template 5.41), CCSDS
+packing (Template 5.42) or as IEEE floating-point numbers (Template 5.4),
+or removes grid points from it using a bit map.
+
+JPEG 2000 code streams are encoded with `opj_compress` of OpenJPEG, which
+must be found in PATH.
+
+The packed integers of the original field are kept as they are, so the
+repacked field decodes to exactly the same values as the original one,
+except grid points replaced with missing values.
+
+This script is intended to generate test data for decoders and is not
+meant to be a general-purpose encoder.
+"""
+
+import argparse
+import lzma
+import os
+import struct
+import subprocess
+import sys
+import tempfile
+import zlib
+
+
+def read_sections(buf):
+    sections = [(0, buf[:16])]
+    pos = 16
+    while buf[pos:pos + 4] != b"7777":
+        (size,) = struct.unpack(">I", buf[pos:pos + 4])
+        sections.append((buf[pos + 4], buf[pos:pos + size]))
+        pos += size
+    return sections
+
+
+def submessage(sections, index):
+    """Returns sections 0, 1 and 3 and sections 4 to 7 of the submessage
+    specified by `index`."""
+    head = [s for s in sections if s[0] in (0, 1, 3)][:3]
+    fours = [i for i, s in enumerate(sections) if s[0] == 4]
+    start = fours[index]
+    return head + sections[start:start + 4]
+
+
+class BitWriter:
+    def __init__(self):
+        self.bits = []
+
+    def write(self, value, nbit):
+        for i in reversed(range(nbit)):
+            self.bits.append((value >> i) & 1)
+
+    def align(self):
+        while len(self.bits) % 8 != 0:
+            self.bits.append(0)
+
+    def to_bytes(self):
+        self.align()
+        out = bytearray()
+        for i in range(0, len(self.bits), 8):
+            byte = 0
+            for bit in self.bits[i:i + 8]:
+                byte = byte << 1 | bit
+            out.append(byte)
+        return bytes(out)
+
+
+def grib_int(value, nbyte):
+    """Converts a sign-magnitude integer into a Python integer."""
+    sign_bit = 1 << (8 * nbyte - 1)
+    return -(value & ~sign_bit) if value & sign_bit else value
+
+
+def unpack_simple(sect5, sect7):
+    num_points = struct.unpack(">I", sect5[5:9])[0]
+    nbit = sect5[19]
+    if nbit != 16:
+        sys.exit("only 16-bit simple packing is supported")
+    return [
+        struct.unpack(">H", sect7[5 + 2 * i:7 + 2 * i])[0] for i in range(num_points)
+    ]
+
+
+def nbit_for(value):
+    return value.bit_length()
+
+
+def split_groups(values):
+    """Splits values into groups of lengths cycling from 5 to 40."""
+    groups = []
+    pos = 0
+    length = 5
+    while pos < len(values):
+        groups.append(values[pos:pos + length])
+        pos += length
+        length = length + 7 if length < 40 else 5
+    return groups
+
+
+def pack_complex(values, missing_management):
+    """Packs values, which are integers or None for primary missing
+    values or "secondary" for secondary missing values, with complex
+    packing and returns the template octets 20-47 without the
+    reference value, the scale factors and the number of bits, and the
+    data octets of Section 7."""
+    num_missing_codes = missing_management
+    groups = split_groups(values)
+
+    refs = []
+    widths = []
+    for group in groups:
+        valid = [v for v in group if isinstance(v, int)]
+        if not valid:
+            if all(v == group[0] for v in group):
+                refs.append(None)
+                widths.append(0)
+            else:
+                refs.append(0)
+                widths.append(nbit_for(num_missing_codes))
+            continue
+        ref = min(valid)
+        max_diff = max(valid) - ref
+        has_missing = len(valid) != len(group)
+        if max_diff == 0 and not has_missing:
+            widths.append(0)
+        else:
+            widths.append(nbit_for(max_diff + num_missing_codes))
+        refs.append(ref)
+
+    max_ref = max([r for r in refs if r is not None] + [0])
+    ref_nbit = nbit_for(max_ref + num_missing_codes)
+
+    def missing_code(nbit, value):
+        primary = (1 << nbit) - 1
+        return primary if value is None else primary - 1
+
+    group_refs = []
+    for ref, group in zip(refs, groups):
+        if ref is None:
+            group_refs.append(missing_code(ref_nbit, group[0]))
+        else:
+            group_refs.append(ref)
+
+    width_ref = min(widths)
+    width_nbit = nbit_for(max(widths) - width_ref)
+    lengths = [len(g) for g in groups]
+    len_ref = min(lengths[:-1] or [0])
+    len_nbit = nbit_for(max(lengths[:-1] or [0]) - len_ref)
+
+    writer = BitWriter()
+    for ref in group_refs:
+        writer.write(ref, ref_nbit)
+    writer.align()
+    for width in widths:
+        writer.write(width - width_ref, width_nbit)
+    writer.align()
+    for length in lengths[:-1]:
+        writer.write(length - len_ref, len_nbit)
+    # The scaled length of the last group is not used.
+    writer.write(0, len_nbit)
+    writer.align()
+    for ref, width, group in zip(refs, widths, groups):
+        if width == 0:
+            continue
+        for value in group:
+            if isinstance(value, int):
+                writer.write(value - ref, width)
+            else:
+                writer.write(missing_code(width, value), width)
+
+    template = struct.pack(
+        ">BBBBffIBBIBIB",
+        ref_nbit,
+        0,  # type of original field values
+        1,  # general group splitting
+        missing_management,
+        0.0,  # primary missing value substitute
+        0.0,  # secondary missing value substitute
+        len(groups),
+        width_ref,
+        width_nbit,
+        len_ref,
+        1,  # length increment
+        lengths[-1],
+        len_nbit,
+    )
+    return template, writer.to_bytes()
+
+
+def spatial_differencing(values, order):
+    """Takes differences of non-missing values and returns the
+    differences and the extra descriptors of Template 5.3, which are the
+    first values and the overall minimum of the differences."""
+    indices = [i for i, v in enumerate(values) if isinstance(v, int)]
+    valid = [values[i] for i in indices]
+    diffs = list(valid)
+    for _ in range(order):
+        diffs = diffs[:1] + [b - a for a, b in zip(diffs, diffs[1:])]
+    first_values = valid[:order]
+    minimum = min(diffs[order:])
+
+    out = list(values)
+    for n, i in enumerate(indices):
+        out[i] = 0 if n < order else diffs[n] - minimum
+
+    # All extra descriptors are sign-magnitude integers, as read by NCEP
+    # g2clib (comunpack.c) and wgrib2 (unpk_complex.c).
+    descriptors = first_values + [minimum]
+    num_octets = 1
+    while max(abs(v) for v in descriptors) >= 1 << (8 * num_octets - 1):
+        num_octets += 1
+
+    writer = BitWriter()
+    for value in descriptors:
+        writer.write(1 if value < 0 else 0, 1)
+        writer.write(abs(value), 8 * num_octets - 1)
+    return out, num_octets, writer.to_bytes()
+
+
+def png_chunk(chunk_type, data):
+    body = chunk_type + data
+    return struct.pack(">I", len(data)) + body + struct.pack(">I", zlib.crc32(body))
+
+
+def pack_png(values, width, height):
+    """Packs 16-bit values into a grayscale PNG image."""
+    rows = b"".join(
+        b"\x00" + struct.pack(">%dH" % width, *values[y * width:(y + 1) * width])
+        for y in range(height)
+    )
+    return (
+        b"\x89PNG\r\n\x1a\n"
+        + png_chunk(b"IHDR", struct.pack(">IIBBBBB", width, height, 16, 0, 0, 0, 0))
+        + png_chunk(b"IDAT", zlib.compress(rows))
+        + png_chunk(b"IEND", b"")
+    )
+
+
+def pack_jpeg2000(values, width, height, nbit):
+    """Packs values into a lossless JPEG 2000 code stream using
+    `opj_compress` with a grayscale PGM image as input."""
+    maxval = (1 << nbit) - 1
+    fmt = ">%dH" if maxval > 255 else ">%dB"
+    with tempfile.TemporaryDirectory() as tmpdir:
+        pgm = os.path.join(tmpdir, "field.pgm")
+        j2k = os.path.join(tmpdir, "field.j2k")
+        with open(pgm, "wb") as f:
+            f.write(b"P5\n%d %d\n%d\n" % (width, height, maxval))
+            f.write(struct.pack(fmt % len(values), *values))
+        subprocess.run(
+            ["opj_compress", "-i", pgm, "-o", j2k], check=True, stdout=subprocess.DEVNULL
+        )
+        with open(j2k, "rb") as f:
+            return f.read()
+
+
+AEC_DATA_3BYTE = 2
+AEC_DATA_MSB = 4
+AEC_DATA_PREPROCESS = 8
+AEC_PAD_RSI = 32
+
+
+def map_residual(predicted, value, xmax):
+    """Maps a prediction residual to a non-negative integer as the
+    preprocessor of CCSDS 121.0-B does."""
+    residual = value - predicted
+    theta = min(predicted, xmax - predicted)
+    if 0 <= residual <= theta:
+        return 2 * residual
+    if -theta <= residual < 0:
+        return -2 * residual - 1
+    return theta + abs(residual)
+
+
+def se_length(residuals):
+    """Returns the number of bits of residuals coded with the second
+    extension option."""
+    length = 1
+    for a, b in zip(residuals[::2], residuals[1::2]):
+        length += (a + b) * (a + b + 1) // 2 + b + 1
+    return length
+
+
+def write_fs(writer, count):
+    writer.write(1, count + 1)
+
+
+def pack_ccsds(values, nbit, block_size, rsi, flags):
+    """Packs 16-bit values with the adaptive entropy coder of CCSDS
+    121.0-B with preprocessing, choosing the shortest coding option for
+    each block."""
+    id_len = 4
+    xmax = (1 << nbit) - 1
+    values = list(values)
+    while len(values) % block_size != 0:
+        values.append(values[-1])
+
+    writer = BitWriter()
+    rsi_len = rsi * block_size
+    for start in range(0, len(values), rsi_len):
+        samples = values[start:start + rsi_len]
+        mapped = [samples[0]] + [
+            map_residual(a, b, xmax) for a, b in zip(samples, samples[1:])
+        ]
+        blocks = [mapped[i:i + block_size] for i in range(0, len(mapped), block_size)]
+        # residuals of each block with the reference sample slot as zero
+        residuals = [[0] + blocks[0][1:]] + blocks[1:]
+
+        b = 0
+        while b < len(blocks):
+            ref = b == 0
+            if not any(residuals[b]):
+                run = 1
+                while (
+                    b + run < len(blocks)
+                    and (b + run) % 64 != 0
+                    and not any(residuals[b + run])
+                ):
+                    run += 1
+                writer.write(0, id_len + 1)
+                if ref:
+                    writer.write(blocks[b][0], nbit)
+                reaches_end = b + run == len(blocks) or (b + run) % 64 == 0
+                if run >= 5 and reaches_end:
+                    write_fs(writer, 4)  # remainder of segment
+                elif run >= 5:
+                    write_fs(writer, run)
+                else:
+                    write_fs(writer, run - 1)
+                b += run
+                continue
+
+            coded = residuals[b][1:] if ref else residuals[b]
+            lengths = [(se_length(residuals[b]), "se")]
+            for k in range((1 << id_len) - 2):
+                lengths.append((sum((r >> k) + 1 + k for r in coded), k))
+            lengths.append((block_size * nbit, "uncompressed"))
+            _, option = min(lengths, key=lambda x: x[0])
+
+            if option == "se":
+                writer.write(1, id_len + 1)
+                if ref:
+                    writer.write(blocks[b][0], nbit)
+                pairs = residuals[b]
+                for x, y in zip(pairs[::2], pairs[1::2]):
+                    write_fs(writer, (x + y) * (x + y + 1) // 2 + y)
+            elif option == "uncompressed":
+                writer.write((1 << id_len) - 1, id_len)
+                for value in blocks[b]:
+                    writer.write(value, nbit)
+            else:
+                writer.write(option + 1, id_len)
+                if ref:
+                    writer.write(blocks[b][0], nbit)
+                for r in coded:
+                    write_fs(writer, r >> option)
+                for r in coded:
+                    writer.write(r & ((1 << option) - 1), option)
+            b += 1
+
+        if flags & AEC_PAD_RSI:
+            writer.align()
+
+    return writer.to_bytes()
+
+
+def set_missing(values, primary_every, secondary_every):
+    out = []
+    for i, value in enumerate(values):
+        if primary_every and i % primary_every == 0:
+            out.append(None)
+        elif secondary_every and i % secondary_every == 0:
+            out.append("secondary")
+        else:
+            out.append(value)
+    return out
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__)
+    parser.add_argument("input")
+    parser.add_argument("index", type=int)
+    parser.add_argument("output")
+    parser.add_argument("--missing-management", type=int, default=0, choices=[0, 1, 2])
+    parser.add_argument("--primary-missing-every", type=int, default=0)
+    parser.add_argument("--secondary-missing-every", type=int, default=0)
+    parser.add_argument("--spatial-differencing", type=int, default=0, choices=[0, 1, 2])
+    parser.add_argument("--jpeg2000", action="store_true")
+    parser.add_argument("--png", action="store_true")
+    parser.add_argument("--ccsds", action="store_true")
+    parser.add_argument("--block-size", type=int, default=32)
+    parser.add_argument("--rsi", type=int, default=128)
+    parser.add_argument("--pad-rsi", action="store_true")
+    parser.add_argument("--ieee", type=int, choices=[32, 64])
+    parser.add_argument("--bitmap-every", type=int, default=0)
+    args = parser.parse_args()
+
+    opener = lzma.open if args.input.endswith(".xz") else open
+    with opener(args.input, "rb") as f:
+        buf = f.read()
+
+    sections = submessage(read_sections(buf), args.index)
+    sect0, sect1, sect3, sect4, sect5, sect6, sect7 = (s[1] for s in sections)
+    if struct.unpack(">H", sect5[9:11])[0] != 0:
+        sys.exit("only simple packing is supported")
+
+    values = unpack_simple(sect5, sect7)
+    if args.bitmap_every:
+        if args.primary_missing_every or args.secondary_missing_every or args.spatial_differencing:
+            sys.exit("bit maps are used with neither missing values nor spatial differencing")
+        bits = [i % args.bitmap_every != 0 for i in range(len(values))]
+        writer = BitWriter()
+        for bit in bits:
+            writer.write(int(bit), 1)
+        bitmap = writer.to_bytes()
+        sect6 = struct.pack(">IBB", 6 + len(bitmap), 6, 0) + bitmap
+        # the same bit map used by the second field
+        sect6_reused = struct.pack(">IBB", 6, 6, 254)
+
+        values = [value for value, bit in zip(values, bits) if bit]
+        body5 = struct.pack(">I", len(values)) + sect5[9:21]
+        data = struct.pack(">%dH" % len(values), *values)
+        write_fields(
+            args.output,
+            sect0,
+            sect1,
+            sect3,
+            [(sect4, body5, sect6, data), (sect4, body5, sect6_reused, data)],
+        )
+        return
+
+    if args.jpeg2000:
+        if args.primary_missing_every or args.secondary_missing_every or args.spatial_differencing:
+            sys.exit("JPEG 2000 packing supports neither missing values nor spatial differencing")
+        width, height = struct.unpack(">II", sect3[30:38])
+        nbit = sect5[19]
+        # octets 6-21 followed by the type of compression (lossless) and
+        # the target compression ratio (missing)
+        body5 = sect5[5:9] + struct.pack(">H", 40) + sect5[11:21] + struct.pack(">BB", 0, 255)
+        data = pack_jpeg2000(values, width, height, nbit)
+        write(args.output, sect0, [sect1, sect3, sect4, body5, sect6, data])
+        return
+
+    if args.png:
+        if args.primary_missing_every or args.secondary_missing_every or args.spatial_differencing:
+            sys.exit("PNG packing supports neither missing values nor spatial differencing")
+        # numbers of points along a parallel and a meridian in Template 3.0
+        width, height = struct.unpack(">II", sect3[30:38])
+        # octets 6-21 (number of values, template number, reference value,
+        # binary and decimal scale factors, number of bits and type of
+        # original field values)
+        body5 = sect5[5:9] + struct.pack(">H", 41) + sect5[11:21]
+        data = pack_png(values, width, height)
+        write(args.output, sect0, [sect1, sect3, sect4, body5, sect6, data])
+        return
+
+    if args.ieee:
+        if args.primary_missing_every or args.secondary_missing_every or args.spatial_differencing:
+            sys.exit("IEEE floating-point data supports neither missing values nor spatial differencing")
+        (ref_val,) = struct.unpack(">f", sect5[11:15])
+        exp = grib_int(struct.unpack(">H", sect5[15:17])[0], 2)
+        dig = grib_int(struct.unpack(">H", sect5[17:19])[0], 2)
+        # values computed in double precision
+        values = [(ref_val + value * 2.0 ** exp) * 10.0 ** -dig for value in values]
+        fmt, precision = (">%df", 1) if args.ieee == 32 else (">%dd", 2)
+        # octets 6-11 (number of values and template number) followed by
+        # the precision (see Code Table 5.7)
+        body5 = sect5[5:9] + struct.pack(">HB", 4, precision)
+        data = struct.pack(fmt % len(values), *values)
+        write(args.output, sect0, [sect1, sect3, sect4, body5, sect6, data])
+        return
+
+    if args.ccsds:
+        if args.primary_missing_every or args.secondary_missing_every or args.spatial_differencing:
+            sys.exit("CCSDS packing supports neither missing values nor spatial differencing")
+        flags = AEC_DATA_3BYTE | AEC_DATA_MSB | AEC_DATA_PREPROCESS
+        if args.pad_rsi:
+            flags |= AEC_PAD_RSI
+        # octets 6-21 followed by the CCSDS compression options mask, the
+        # block size and the reference sample interval
+        body5 = (
+            sect5[5:9]
+            + struct.pack(">H", 42)
+            + sect5[11:21]
+            + struct.pack(">BBH", flags, args.block_size, args.rsi)
+        )
+        data = pack_ccsds(values, 16, args.block_size, args.rsi, flags)
+        write(args.output, sect0, [sect1, sect3, sect4, body5, sect6, data])
+        return
+
+    values = set_missing(
+        values, args.primary_missing_every, args.secondary_missing_every
+    )
+    tmpl_num = 2
+    descriptors = b""
+    order = args.spatial_differencing
+    if order:
+        values, num_octets, descriptors = spatial_differencing(values, order)
+        tmpl_num = 3
+
+    template, data = pack_complex(values, args.missing_management)
+    data = descriptors + data
+    if order:
+        template += struct.pack(">BB", order, num_octets)
+
+    # octets 6-19 (number of values, template number, reference value,
+    # binary and decimal scale factors) followed by the number of bits
+    # for group reference values and the rest of the template
+    body5 = sect5[5:9] + struct.pack(">H", tmpl_num) + sect5[11:19] + template
+    write(args.output, sect0, [sect1, sect3, sect4, body5, sect6, data])
+
+
+def write(output, sect0, sections):
+    """Writes a message consisting of `sect0` and `sections`, which are
+    sections 1, 3, 4 and 6 as they are and the bodies of sections 5 and
+    7."""
+    sect1, sect3, sect4, body5, sect6, body7 = sections
+    write_fields(output, sect0, sect1, sect3, [(sect4, body5, sect6, body7)])
+
+
+def write_fields(output, sect0, sect1, sect3, fields):
+    """Writes a message with fields sharing sections 1 and 3, each of
+    which consists of sections 4 and 6 and the bodies of sections 5 and
+    7."""
+    body = sect1 + sect3
+    for sect4, body5, sect6, body7 in fields:
+        new5 = struct.pack(">IB", 5 + len(body5), 5) + body5
+        new7 = struct.pack(">IB", 5 + len(body7), 7) + body7
+        body += sect4 + new5 + sect6 + new7
+
+    total = 16 + len(body) + 4
+    sect0 = sect0[:8] + struct.pack(">Q", total)
+
+    with lzma.open(output, "wb") if output.endswith(".xz") else open(output, "wb") as f:
+        f.write(sect0 + body + b"7777")
+
+
+if __name__ == "__main__":
+    main()
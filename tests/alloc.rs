@@ -0,0 +1,77 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+#[allow(dead_code)]
+mod utils;
+
+/// Allocator counting allocations made by each thread, so that tests
+/// running in parallel do not affect each other's counts.
+struct CountingAllocator;
+
+thread_local! {
+    static NUM_ALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_alloc() {
+    let _ = NUM_ALLOCS.try_with(|n| n.set(n.get() + 1));
+}
+
+fn num_allocs() -> usize {
+    NUM_ALLOCS.with(|n| n.get())
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_alloc();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_alloc();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_alloc();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// Templates 5.1, 5.40, 5.41, 5.42, 5.50, 5.51 and 5.200 are not decoded
+// into buffers yet and are not covered here.
+#[test]
+fn decoding_into_buffers_does_not_allocate() {
+    for file_name in [
+        "Z__C_RJTD_20170221120000_MSG_GPV_Gll0p5deg_Pys_B20170221120000_F2017022115-2017022212_grib2.bin.xz",
+        "gen/kousa-complex.grib2.xz",
+        "gen/kousa-complex-diff2-missing.grib2.xz",
+        "gen/kousa-ieee64.grib2.xz",
+        "gen/kousa-bitmap.grib2.xz",
+    ]
+    .iter()
+    {
+        let grib = utils::grib_from(utils::testdata_dir().join(file_name));
+        let num_submessages = grib.submessages().len();
+        let mut out = vec![0_f32; grib.get_values(0).unwrap().len()];
+
+        // Buffers grow to the sizes needed in the first pass.
+        for i in 0..num_submessages {
+            grib.get_values_into(i, &mut out).unwrap();
+        }
+
+        let before = num_allocs();
+        for _ in 0..3 {
+            for i in 0..num_submessages {
+                grib.get_values_into(i, &mut out).unwrap();
+            }
+        }
+        assert_eq!(num_allocs(), before, "{}", file_name);
+    }
+}
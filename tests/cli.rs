@@ -5,7 +5,7 @@ use tempfile::TempDir;
 
 mod utils;
 
-const CMD_NAME: &'static str = "gribber";
+const CMD_NAME: &str = "gribber";
 
 #[test]
 fn help() -> Result<(), Box<dyn std::error::Error>> {
@@ -28,7 +28,7 @@ fn no_subcommand_specified() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::cargo_bin(CMD_NAME)?;
     cmd.arg("--help");
     let help_msg = cmd.output()?.stdout;
-    let help_msg = format!("{}", String::from_utf8(help_msg)?);
+    let help_msg = String::from_utf8(help_msg)?;
 
     let mut cmd = Command::cargo_bin(CMD_NAME)?;
     cmd.assert()
@@ -84,7 +84,32 @@ Type of processed data:                 Analysis and forecast products
 }
 
 #[test]
-fn list() -> Result<(), Box<dyn std::error::Error>> {
+fn list_summary() -> Result<(), Box<dyn std::error::Error>> {
+    let tempfile = utils::jma_tornado_nowcast_file()?;
+    let arg_path = tempfile.path();
+
+    let out_str = "\
+0  4.0  0.193.0  0 min  Ground or water surface
+1  4.0  0.193.0  10 min  Ground or water surface
+2  4.0  0.193.0  20 min  Ground or water surface
+3  4.0  0.193.0  30 min  Ground or water surface
+4  4.0  0.193.0  40 min  Ground or water surface
+5  4.0  0.193.0  50 min  Ground or water surface
+6  4.0  0.193.0  60 min  Ground or water surface
+";
+
+    let mut cmd = Command::cargo_bin(CMD_NAME)?;
+    cmd.arg("list").arg(arg_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::similar(out_str))
+        .stderr(predicate::str::is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn list_dump() -> Result<(), Box<dyn std::error::Error>> {
     let tempfile = utils::jma_tornado_nowcast_file()?;
     let arg_path = tempfile.path();
 
@@ -220,7 +245,7 @@ fn list() -> Result<(), Box<dyn std::error::Error>> {
 ";
 
     let mut cmd = Command::cargo_bin(CMD_NAME)?;
-    cmd.arg("list").arg(arg_path);
+    cmd.arg("list").arg("-d").arg(arg_path);
     cmd.assert()
         .success()
         .stdout(predicate::str::similar(out_str))
@@ -364,7 +389,7 @@ fn inspect_with_all_opts() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::cargo_bin(CMD_NAME)?;
     cmd.arg("inspect").arg(arg_path);
     let msg_no_opt = cmd.output()?.stdout;
-    let msg_no_opt = format!("{}", String::from_utf8(msg_no_opt)?);
+    let msg_no_opt = String::from_utf8(msg_no_opt)?;
 
     let mut cmd = Command::cargo_bin(CMD_NAME)?;
     cmd.arg("inspect").arg("-s").arg("-t").arg(arg_path);
@@ -423,12 +448,10 @@ fn decode_tornado_big_endian() -> Result<(), Box<dyn std::error::Error>> {
     let expected = utils::tornado_nowcast_be_bin_bytes()?;
     let expected: Vec<_> = expected
         .chunks(4)
-        .into_iter()
-        .map(|b| match b {
+        .flat_map(|b| match b {
             [0x62, 0x58, 0xd1, 0x9a] => vec![0x7f, 0xc0, 0x00, 0x00],
             b => b.to_vec(),
         })
-        .flatten()
         .collect();
     let actual = utils::cat_as_bytes(&out_path)?;
     assert_eq!(actual, expected);
@@ -436,6 +459,54 @@ fn decode_tornado_big_endian() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn decode_tornado_big_endian_with_missing_value() -> Result<(), Box<dyn std::error::Error>> {
+    let tempfile = utils::jma_tornado_nowcast_file()?;
+    let arg_path = tempfile.path();
+
+    let dir = TempDir::new()?;
+    let out_path = dir.path().join("out.bin");
+    let out_path = format!("{}", out_path.display());
+
+    let mut cmd = Command::cargo_bin(CMD_NAME)?;
+    cmd.arg("decode")
+        .arg(arg_path)
+        .arg("3")
+        .arg("-b")
+        .arg(&out_path)
+        .arg("--missing")
+        .arg("9.999e20");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    let expected = utils::tornado_nowcast_be_bin_bytes()?;
+    let actual = utils::cat_as_bytes(&out_path)?;
+    assert_eq!(actual, expected);
+
+    Ok(())
+}
+
+#[test]
+fn decode_with_invalid_missing_value() -> Result<(), Box<dyn std::error::Error>> {
+    let tempfile = utils::jma_tornado_nowcast_file()?;
+    let arg_path = tempfile.path();
+
+    let mut cmd = Command::cargo_bin(CMD_NAME)?;
+    cmd.arg("decode")
+        .arg(arg_path)
+        .arg("3")
+        .arg("--missing")
+        .arg("missing");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("invalid missing value: missing"));
+
+    Ok(())
+}
+
 #[test]
 fn decode_tornado_little_endian() -> Result<(), Box<dyn std::error::Error>> {
     let tempfile = utils::jma_tornado_nowcast_file()?;
@@ -459,12 +530,10 @@ fn decode_tornado_little_endian() -> Result<(), Box<dyn std::error::Error>> {
     let expected = utils::tornado_nowcast_le_bin_bytes()?;
     let expected: Vec<_> = expected
         .chunks(4)
-        .into_iter()
-        .map(|b| match b {
+        .flat_map(|b| match b {
             [0x9a, 0xd1, 0x58, 0x62] => vec![0x00, 0x00, 0xc0, 0x7f],
             b => b.to_vec(),
         })
-        .flatten()
         .collect();
     let actual = utils::cat_as_bytes(&out_path)?;
     assert_eq!(actual, expected);
@@ -526,6 +595,331 @@ fn decode_kousa_little_endian() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn decode_kousa_complex_packing_big_endian() -> Result<(), Box<dyn std::error::Error>> {
+    let tempfile = utils::kousa_complex_packing_file()?;
+    let arg_path = tempfile.path();
+
+    let dir = TempDir::new()?;
+    let out_path = dir.path().join("out.bin");
+    let out_path = format!("{}", out_path.display());
+
+    let mut cmd = Command::cargo_bin(CMD_NAME)?;
+    cmd.arg("decode")
+        .arg(arg_path)
+        .arg("0")
+        .arg("-b")
+        .arg(&out_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    let expected = utils::kousa_be_bin_bytes()?;
+    let actual = utils::cat_as_bytes(&out_path)?;
+    assert_eq!(actual, expected);
+
+    Ok(())
+}
+
+#[cfg(feature = "jpeg2000")]
+#[test]
+fn decode_kousa_jpeg2000_big_endian() -> Result<(), Box<dyn std::error::Error>> {
+    let tempfile = utils::kousa_jpeg2000_file()?;
+    let arg_path = tempfile.path();
+
+    let dir = TempDir::new()?;
+    let out_path = dir.path().join("out.bin");
+    let out_path = format!("{}", out_path.display());
+
+    let mut cmd = Command::cargo_bin(CMD_NAME)?;
+    cmd.arg("decode")
+        .arg(arg_path)
+        .arg("0")
+        .arg("-b")
+        .arg(&out_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    let expected = utils::kousa_be_bin_bytes()?;
+    let actual = utils::cat_as_bytes(&out_path)?;
+    assert_eq!(actual, expected);
+
+    Ok(())
+}
+
+#[test]
+fn decode_kousa_png_big_endian() -> Result<(), Box<dyn std::error::Error>> {
+    let tempfile = utils::kousa_png_file()?;
+    let arg_path = tempfile.path();
+
+    let dir = TempDir::new()?;
+    let out_path = dir.path().join("out.bin");
+    let out_path = format!("{}", out_path.display());
+
+    let mut cmd = Command::cargo_bin(CMD_NAME)?;
+    cmd.arg("decode")
+        .arg(arg_path)
+        .arg("0")
+        .arg("-b")
+        .arg(&out_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    let expected = utils::kousa_be_bin_bytes()?;
+    let actual = utils::cat_as_bytes(&out_path)?;
+    assert_eq!(actual, expected);
+
+    Ok(())
+}
+
+#[test]
+fn decode_kousa_ccsds_big_endian() -> Result<(), Box<dyn std::error::Error>> {
+    let tempfile = utils::kousa_ccsds_file()?;
+    let arg_path = tempfile.path();
+
+    let dir = TempDir::new()?;
+    let out_path = dir.path().join("out.bin");
+    let out_path = format!("{}", out_path.display());
+
+    let mut cmd = Command::cargo_bin(CMD_NAME)?;
+    cmd.arg("decode")
+        .arg(arg_path)
+        .arg("0")
+        .arg("-b")
+        .arg(&out_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    let expected = utils::kousa_be_bin_bytes()?;
+    let actual = utils::cat_as_bytes(&out_path)?;
+    assert_eq!(actual, expected);
+
+    Ok(())
+}
+
+#[test]
+fn decode_kousa_ccsds_with_short_blocks() -> Result<(), Box<dyn std::error::Error>> {
+    // This file contains the 7th field of the kousa file, whose blocks
+    // are coded with all the options including zero blocks and the second
+    // extension.
+    let dir = TempDir::new()?;
+    let mut outputs = Vec::new();
+    for (tempfile, index) in [
+        (utils::jma_kousa_file()?, "6"),
+        (utils::kousa_ccsds_with_short_blocks_file()?, "0"),
+    ]
+    .iter()
+    {
+        let out_path = dir.path().join(format!("out{}.bin", outputs.len()));
+        let out_path = format!("{}", out_path.display());
+
+        let mut cmd = Command::cargo_bin(CMD_NAME)?;
+        cmd.arg("decode")
+            .arg(tempfile.path())
+            .arg(index)
+            .arg("-b")
+            .arg(&out_path);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::is_empty())
+            .stderr(predicate::str::is_empty());
+        outputs.push(utils::cat_as_bytes(&out_path)?);
+    }
+
+    assert_eq!(outputs[1], outputs[0]);
+
+    Ok(())
+}
+
+#[test]
+fn decode_kousa_with_bitmap() -> Result<(), Box<dyn std::error::Error>> {
+    // This file contains two fields of the 4th field of the kousa file
+    // without every 5th grid point, the first of which has a bit map and
+    // the second of which reuses it.
+    let dir = TempDir::new()?;
+    let mut outputs = Vec::new();
+    for (tempfile, index) in [
+        (utils::jma_kousa_file()?, "3"),
+        (utils::kousa_bitmap_file()?, "0"),
+        (utils::kousa_bitmap_file()?, "1"),
+    ]
+    .iter()
+    {
+        let out_path = dir.path().join(format!("out{}.bin", outputs.len()));
+        let out_path = format!("{}", out_path.display());
+
+        let mut cmd = Command::cargo_bin(CMD_NAME)?;
+        cmd.arg("decode")
+            .arg(tempfile.path())
+            .arg(index)
+            .arg("-b")
+            .arg(&out_path);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::is_empty())
+            .stderr(predicate::str::is_empty());
+        outputs.push(utils::cat_as_bytes(&out_path)?);
+    }
+
+    let expected = outputs[0]
+        .chunks(4)
+        .enumerate()
+        .flat_map(|(i, bytes)| match i % 5 {
+            0 => f32::NAN.to_be_bytes().to_vec(),
+            _ => bytes.to_vec(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(outputs[1], expected);
+    assert_eq!(outputs[2], expected);
+
+    Ok(())
+}
+
+#[test]
+fn decode_kousa_ieee_float_big_endian() -> Result<(), Box<dyn std::error::Error>> {
+    for precision in [32, 64].iter() {
+        let tempfile = utils::kousa_ieee_float_file(*precision)?;
+        let arg_path = tempfile.path();
+
+        let dir = TempDir::new()?;
+        let out_path = dir.path().join("out.bin");
+        let out_path = format!("{}", out_path.display());
+
+        let mut cmd = Command::cargo_bin(CMD_NAME)?;
+        cmd.arg("decode")
+            .arg(arg_path)
+            .arg("0")
+            .arg("-b")
+            .arg(&out_path);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::is_empty())
+            .stderr(predicate::str::is_empty());
+
+        let expected = utils::kousa_be_bin_bytes()?;
+        let actual = utils::cat_as_bytes(&out_path)?;
+        assert_eq!(actual, expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn decode_kousa_complex_packing_with_missing_values() -> Result<(), Box<dyn std::error::Error>> {
+    let tempfile = utils::kousa_complex_packing_with_missing_file()?;
+    let arg_path = tempfile.path();
+
+    let dir = TempDir::new()?;
+    let out_path = dir.path().join("out.bin");
+    let out_path = format!("{}", out_path.display());
+
+    let mut cmd = Command::cargo_bin(CMD_NAME)?;
+    cmd.arg("decode")
+        .arg(arg_path)
+        .arg("0")
+        .arg("-b")
+        .arg(&out_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    // Every 7th value is replaced with the primary missing value and
+    // every 11th value with the secondary missing value.
+    let expected = utils::kousa_be_bin_bytes()?;
+    let expected: Vec<_> = expected
+        .chunks(4)
+        .enumerate()
+        .flat_map(|(i, b)| {
+            if i % 7 == 0 || i % 11 == 0 {
+                vec![0x7f, 0xc0, 0x00, 0x00]
+            } else {
+                b.to_vec()
+            }
+        })
+        .collect();
+    let actual = utils::cat_as_bytes(&out_path)?;
+    assert_eq!(actual, expected);
+
+    Ok(())
+}
+
+#[test]
+fn decode_kousa_complex_packing_with_first_order_differencing(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let tempfile = utils::kousa_complex_packing_first_order_differencing_file()?;
+    let arg_path = tempfile.path();
+
+    let dir = TempDir::new()?;
+    let out_path = dir.path().join("out.bin");
+    let out_path = format!("{}", out_path.display());
+
+    let mut cmd = Command::cargo_bin(CMD_NAME)?;
+    cmd.arg("decode")
+        .arg(arg_path)
+        .arg("0")
+        .arg("-b")
+        .arg(&out_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    let expected = utils::kousa_be_bin_bytes()?;
+    let actual = utils::cat_as_bytes(&out_path)?;
+    assert_eq!(actual, expected);
+
+    Ok(())
+}
+
+#[test]
+fn decode_kousa_complex_packing_with_second_order_differencing_and_missing_values(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let tempfile = utils::kousa_complex_packing_second_order_differencing_with_missing_file()?;
+    let arg_path = tempfile.path();
+
+    let dir = TempDir::new()?;
+    let out_path = dir.path().join("out.bin");
+    let out_path = format!("{}", out_path.display());
+
+    let mut cmd = Command::cargo_bin(CMD_NAME)?;
+    cmd.arg("decode")
+        .arg(arg_path)
+        .arg("0")
+        .arg("-b")
+        .arg(&out_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    // Every 7th value is replaced with the primary missing value and
+    // every 11th value with the secondary missing value.
+    let expected = utils::kousa_be_bin_bytes()?;
+    let expected: Vec<_> = expected
+        .chunks(4)
+        .enumerate()
+        .flat_map(|(i, b)| {
+            if i % 7 == 0 || i % 11 == 0 {
+                vec![0x7f, 0xc0, 0x00, 0x00]
+            } else {
+                b.to_vec()
+            }
+        })
+        .collect();
+    let actual = utils::cat_as_bytes(&out_path)?;
+    assert_eq!(actual, expected);
+
+    Ok(())
+}
+
 macro_rules! test_subcommands_without_args {
     ($(($name:ident, $str:expr),)*) => ($(
         #[test]
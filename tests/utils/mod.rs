@@ -1,5 +1,7 @@
+use grib::context::Grib2;
+use grib::reader::SeekableGrib2Reader;
 use std::fs::File;
-use std::io::{self, BufReader, Read, Write};
+use std::io::{self, BufReader, Cursor, Read, Write};
 use std::path::{Path, PathBuf};
 use tempfile::NamedTempFile;
 use xz2::bufread::XzDecoder;
@@ -18,6 +20,69 @@ pub(crate) fn jma_kousa_file() -> Result<NamedTempFile, io::Error> {
     )
 }
 
+pub(crate) fn kousa_complex_packing_file() -> Result<NamedTempFile, io::Error> {
+    unxz_to_tempfile(testdata_dir().join("gen").join("kousa-complex.grib2.xz"))
+}
+
+pub(crate) fn kousa_complex_packing_with_missing_file() -> Result<NamedTempFile, io::Error> {
+    unxz_to_tempfile(
+        testdata_dir()
+            .join("gen")
+            .join("kousa-complex-missing.grib2.xz"),
+    )
+}
+
+pub(crate) fn kousa_complex_packing_first_order_differencing_file(
+) -> Result<NamedTempFile, io::Error> {
+    unxz_to_tempfile(
+        testdata_dir()
+            .join("gen")
+            .join("kousa-complex-diff1.grib2.xz"),
+    )
+}
+
+pub(crate) fn kousa_complex_packing_second_order_differencing_with_missing_file(
+) -> Result<NamedTempFile, io::Error> {
+    unxz_to_tempfile(
+        testdata_dir()
+            .join("gen")
+            .join("kousa-complex-diff2-missing.grib2.xz"),
+    )
+}
+
+#[cfg(feature = "jpeg2000")]
+pub(crate) fn kousa_jpeg2000_file() -> Result<NamedTempFile, io::Error> {
+    unxz_to_tempfile(testdata_dir().join("gen").join("kousa-jpeg2000.grib2.xz"))
+}
+
+pub(crate) fn kousa_png_file() -> Result<NamedTempFile, io::Error> {
+    unxz_to_tempfile(testdata_dir().join("gen").join("kousa-png.grib2.xz"))
+}
+
+pub(crate) fn kousa_ccsds_file() -> Result<NamedTempFile, io::Error> {
+    unxz_to_tempfile(testdata_dir().join("gen").join("kousa-ccsds.grib2.xz"))
+}
+
+pub(crate) fn kousa_ccsds_with_short_blocks_file() -> Result<NamedTempFile, io::Error> {
+    unxz_to_tempfile(
+        testdata_dir()
+            .join("gen")
+            .join("kousa-ccsds-short-blocks.grib2.xz"),
+    )
+}
+
+pub(crate) fn kousa_bitmap_file() -> Result<NamedTempFile, io::Error> {
+    unxz_to_tempfile(testdata_dir().join("gen").join("kousa-bitmap.grib2.xz"))
+}
+
+pub(crate) fn kousa_ieee_float_file(precision: usize) -> Result<NamedTempFile, io::Error> {
+    unxz_to_tempfile(
+        testdata_dir()
+            .join("gen")
+            .join(format!("kousa-ieee{}.grib2.xz", precision)),
+    )
+}
+
 fn unxz_to_tempfile(file_path: PathBuf) -> Result<NamedTempFile, io::Error> {
     let mut buf = Vec::new();
     let mut out = NamedTempFile::new()?;
@@ -47,6 +112,13 @@ pub(crate) fn kousa_le_bin_bytes() -> Result<Vec<u8>, io::Error> {
     unxz_as_bytes(testdata_dir().join("gen").join("kousa-wgrib2-le.bin.xz"))
 }
 
+// Used by the allocation tests but not by the CLI tests.
+#[allow(dead_code)]
+pub(crate) fn grib_from(file_path: PathBuf) -> Grib2<SeekableGrib2Reader<Cursor<Vec<u8>>>> {
+    let f = Cursor::new(unxz_as_bytes(file_path).unwrap());
+    Grib2::<SeekableGrib2Reader<Cursor<Vec<u8>>>>::read_with_seekable(f).unwrap()
+}
+
 fn unxz_as_bytes(file_path: PathBuf) -> Result<Vec<u8>, io::Error> {
     let mut buf = Vec::new();
 
@@ -82,6 +154,6 @@ pub(crate) fn cat_as_bytes(file_name: &str) -> Result<Vec<u8>, io::Error> {
     Ok(buf)
 }
 
-fn testdata_dir() -> &'static Path {
+pub(crate) fn testdata_dir() -> &'static Path {
     Path::new("testdata")
 }